@@ -0,0 +1,20 @@
+//! Asynchronous interface change notifier example.
+
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut if_change_notifier = if_addrs::AsyncIfChangeNotifier::new().unwrap();
+    println!("Waiting for interface changes...");
+    loop {
+        if let Ok(details) = if_change_notifier.next().await {
+            println!("Network interfaces changed: {:#?}", details);
+        }
+    }
+}
+
+#[cfg(not(all(unix, not(any(target_os = "macos", target_os = "ios")))))]
+fn main() {
+    panic!(
+        "Asynchronous interface change API is only implemented for Unix other than macOS or iOS"
+    );
+}
@@ -7,16 +7,41 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod neighbor;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod netlink;
 #[cfg(not(windows))]
 mod posix;
 #[cfg(all(not(windows), not(any(target_os = "macos", target_os = "ios"))))]
 mod posix_not_mac;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod route;
 mod sockaddr;
+mod stats;
 #[cfg(windows)]
 mod windows;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use neighbor::{get_neighbors, Neighbor, NeighborState};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use route::{
+    default_gateways, get_routes, get_rules, route_to, DefaultGateway, NextHop, Route, RouteLookup,
+    RouteProtocol, RouteType, Rule, RuleAction,
+};
+pub use stats::{
+    get_all_interface_stats, get_interface_stats, Clock, CounterWidth, InterfaceRates,
+    InterfaceStats, StatsSampler, SystemClock,
+};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::time::Duration;
 
 /// Details about an interface on this host.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -27,6 +52,11 @@ pub struct Interface {
     pub addr: IfAddr,
     /// The index of the interface.
     pub index: Option<u32>,
+    /// The flags describing the state and capabilities of the interface.
+    pub flags: InterfaceFlags,
+    /// The hardware (link-layer) address of the interface, if it has one and
+    /// the OS reports it.
+    pub hw_addr: Option<HardwareAddr>,
     /// (Windows only) A permanent and unique identifier for the interface. It
     /// cannot be modified by the user. It is typically a GUID string of the
     /// form: "{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}", but this is not
@@ -38,7 +68,28 @@ pub struct Interface {
 impl Interface {
     /// Check whether this is a loopback interface.
     pub fn is_loopback(&self) -> bool {
-        self.addr.is_loopback()
+        self.flags.is_loopback()
+    }
+
+    /// Check whether this interface is administratively up.
+    pub fn is_up(&self) -> bool {
+        self.flags.is_up()
+    }
+
+    /// Check whether this interface is operational, i.e. it has resources
+    /// allocated and, where the OS reports it, a carrier.
+    pub fn is_running(&self) -> bool {
+        self.flags.is_running()
+    }
+
+    /// Check whether this is a point-to-point interface, such as a tunnel.
+    pub fn is_point_to_point(&self) -> bool {
+        self.flags.is_point_to_point()
+    }
+
+    /// Check whether this interface supports multicast.
+    pub fn supports_multicast(&self) -> bool {
+        self.flags.supports_multicast()
     }
 
     /// Check whether this is a link local interface.
@@ -50,8 +101,316 @@ impl Interface {
     pub fn ip(&self) -> IpAddr {
         self.addr.ip()
     }
+
+    /// The key of the [`NetworkInterface`] that this address belongs to.
+    fn link_key(&self) -> Option<LinkKey> {
+        #[cfg(windows)]
+        return Some(self.adapter_name.clone());
+        #[cfg(not(windows))]
+        return self.index;
+    }
+}
+
+/// A network interface on this host, along with all of its IP addresses.
+///
+/// Unlike [`Interface`], which describes a single address, this describes each
+/// interface once, including interfaces that have no IP address at all.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct NetworkInterface {
+    /// The name of the interface.
+    pub name: String,
+    /// The index of the interface.
+    pub index: u32,
+    /// The flags describing the state and capabilities of the interface.
+    pub flags: InterfaceFlags,
+    /// The hardware (link-layer) address of the interface, if it has one and
+    /// the OS reports it.
+    pub hw_addr: Option<HardwareAddr>,
+    /// The IP addresses of the interface.
+    pub addrs: Vec<IfAddr>,
+    /// The maximum transmission unit of the interface, where the OS reports
+    /// it.
+    pub mtu: Option<u32>,
+    /// (Linux only) The length of the transmit queue of the interface.
+    pub tx_queue_len: Option<u32>,
+    /// The type of the link layer of the interface, where the OS reports it.
+    pub link_type: Option<LinkType>,
+    /// The operational state of the interface. [`OperState::Unknown`] where
+    /// the OS doesn't report it.
+    pub oper_state: OperState,
+    /// (Linux only) The index of the bond, bridge or VRF interface this
+    /// interface is enslaved to, if any.
+    pub master: Option<u32>,
+    /// (Windows only) A permanent and unique identifier for the interface.
+    /// See [`Interface::adapter_name`].
+    #[cfg(windows)]
+    pub adapter_name: String,
+}
+
+impl NetworkInterface {
+    /// Check whether this is a loopback interface.
+    pub fn is_loopback(&self) -> bool {
+        self.flags.is_loopback()
+    }
+
+    /// Check whether this interface is administratively up.
+    pub fn is_up(&self) -> bool {
+        self.flags.is_up()
+    }
+
+    /// Check whether this interface is operational, i.e. it has resources
+    /// allocated and, where the OS reports it, a carrier.
+    pub fn is_running(&self) -> bool {
+        self.flags.is_running()
+    }
+
+    /// Check whether this is a point-to-point interface, such as a tunnel.
+    pub fn is_point_to_point(&self) -> bool {
+        self.flags.is_point_to_point()
+    }
+
+    /// Check whether this interface supports multicast.
+    pub fn supports_multicast(&self) -> bool {
+        self.flags.supports_multicast()
+    }
+
+    /// Get the IP addresses of this interface.
+    pub fn ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        self.addrs.iter().map(IfAddr::ip)
+    }
+
+    /// Create an entry, without addresses, for the interface of `interface`.
+    fn from_interface(interface: &Interface) -> Option<Self> {
+        Some(Self {
+            name: interface.name.clone(),
+            index: interface.index?,
+            flags: interface.flags,
+            hw_addr: interface.hw_addr,
+            addrs: Vec::new(),
+            mtu: None,
+            tx_queue_len: None,
+            link_type: None,
+            oper_state: OperState::Unknown,
+            master: None,
+            #[cfg(windows)]
+            adapter_name: interface.adapter_name.clone(),
+        })
+    }
+
+    /// The key that [`Interface::link_key`] matches for its addresses.
+    fn key(&self) -> LinkKey {
+        #[cfg(windows)]
+        return self.adapter_name.clone();
+        #[cfg(not(windows))]
+        return self.index;
+    }
+}
+
+/// What identifies the interface an address belongs to. Windows reports
+/// separate IPv4 and IPv6 indexes for an adapter, so its name is used there.
+#[cfg(windows)]
+type LinkKey = String;
+#[cfg(not(windows))]
+type LinkKey = u32;
+
+/// The type of the link layer of an interface.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LinkType {
+    /// Ethernet, or a link that emulates it such as a bridge or a Linux
+    /// wireless interface.
+    Ethernet,
+    /// A loopback interface.
+    Loopback,
+    /// An IEEE 802.11 wireless interface.
+    Ieee80211,
+    /// A point-to-point protocol link.
+    Ppp,
+    /// An IP tunnel, such as IP-in-IP, SIT or GRE.
+    Tunnel,
+    /// A controller area network (SocketCAN) interface.
+    Can,
+    /// An InfiniBand interface.
+    Infiniband,
+    /// A link without a link-layer header, such as a Linux tun device.
+    None,
+    /// Any other link type, as the raw `ARPHRD_*` value on Linux or `IF_TYPE_*`
+    /// value on Windows.
+    Other(u32),
+}
+
+/// The operational state of an interface, as defined by RFC 2863.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum OperState {
+    /// The state is not known, or not reported by the OS. Some interfaces,
+    /// such as loopback on Linux, are always in this state.
+    Unknown,
+    /// A component of the interface, typically hardware, is missing.
+    NotPresent,
+    /// The interface is down, e.g. because it was disabled.
+    Down,
+    /// The interface is down because of the interface it runs on, e.g. a
+    /// port with no cable, or a VLAN on top of a down interface.
+    LowerLayerDown,
+    /// The interface is in a test mode.
+    Testing,
+    /// The interface is waiting for an external event, such as 802.1X
+    /// authentication.
+    Dormant,
+    /// The interface is operational and can pass packets.
+    Up,
+}
+
+bitflags::bitflags! {
+    /// The state and capabilities of an interface, as reported by the OS.
+    ///
+    /// On POSIX systems these mirror the `IFF_*` flags of `getifaddrs`. On
+    /// Windows they are derived from the adapter's type, operational status
+    /// and flags, and `PROMISC` is never set.
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+    pub struct InterfaceFlags: u32 {
+        /// The interface is administratively up.
+        const UP = 1 << 0;
+        /// The interface has a valid broadcast address.
+        const BROADCAST = 1 << 1;
+        /// The interface is a loopback interface.
+        const LOOPBACK = 1 << 2;
+        /// The interface is a point-to-point link.
+        const POINTOPOINT = 1 << 3;
+        /// The interface is operational.
+        const RUNNING = 1 << 4;
+        /// The interface supports multicast.
+        const MULTICAST = 1 << 5;
+        /// The interface is in promiscuous mode.
+        const PROMISC = 1 << 6;
+    }
+}
+
+impl InterfaceFlags {
+    /// Check whether the interface is administratively up.
+    pub fn is_up(&self) -> bool {
+        self.contains(InterfaceFlags::UP)
+    }
+
+    /// Check whether the interface is operational.
+    pub fn is_running(&self) -> bool {
+        self.contains(InterfaceFlags::RUNNING)
+    }
+
+    /// Check whether the interface is a loopback interface.
+    pub fn is_loopback(&self) -> bool {
+        self.contains(InterfaceFlags::LOOPBACK)
+    }
+
+    /// Check whether the interface is a point-to-point link.
+    pub fn is_point_to_point(&self) -> bool {
+        self.contains(InterfaceFlags::POINTOPOINT)
+    }
+
+    /// Check whether the interface supports broadcast.
+    pub fn supports_broadcast(&self) -> bool {
+        self.contains(InterfaceFlags::BROADCAST)
+    }
+
+    /// Check whether the interface supports multicast.
+    pub fn supports_multicast(&self) -> bool {
+        self.contains(InterfaceFlags::MULTICAST)
+    }
+
+    /// Check whether the interface is in promiscuous mode.
+    pub fn is_promiscuous(&self) -> bool {
+        self.contains(InterfaceFlags::PROMISC)
+    }
 }
 
+/// A hardware (link-layer) address, such as an Ethernet MAC address.
+///
+/// Addresses of up to [`HardwareAddr::MAX_LEN`] bytes are supported, which
+/// covers link types with longer addresses such as 20-byte InfiniBand
+/// addresses. It is formatted and parsed as colon-separated hex bytes, e.g.
+/// `00:1b:21:3a:4f:5c`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct HardwareAddr {
+    bytes: [u8; HardwareAddr::MAX_LEN],
+    len: u8,
+}
+
+impl HardwareAddr {
+    /// The maximum length of a hardware address, in bytes.
+    pub const MAX_LEN: usize = 32;
+
+    /// Create a hardware address from its bytes. Returns `None` if `bytes`
+    /// is empty or longer than [`HardwareAddr::MAX_LEN`].
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() > Self::MAX_LEN {
+            return None;
+        }
+        let mut addr = Self {
+            bytes: [0; Self::MAX_LEN],
+            len: bytes.len() as u8,
+        };
+        addr.bytes[..bytes.len()].copy_from_slice(bytes);
+        Some(addr)
+    }
+
+    /// Get the bytes of this hardware address.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Get this address as a 6-byte (EUI-48) MAC address, if it is one.
+    pub fn as_mac(&self) -> Option<[u8; 6]> {
+        self.as_bytes().try_into().ok()
+    }
+}
+
+impl fmt::Display for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.as_bytes().iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HardwareAddr({})", self)
+    }
+}
+
+impl FromStr for HardwareAddr {
+    type Err = HardwareAddrParseError;
+
+    /// Parse a hardware address from hex bytes separated by `:` or `-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let separator = if s.contains('-') { '-' } else { ':' };
+        let mut bytes = Vec::new();
+        for part in s.split(separator) {
+            if part.is_empty() || part.len() > 2 {
+                return Err(HardwareAddrParseError(()));
+            }
+            let byte = u8::from_str_radix(part, 16).map_err(|_| HardwareAddrParseError(()))?;
+            bytes.push(byte);
+        }
+        Self::new(&bytes).ok_or(HardwareAddrParseError(()))
+    }
+}
+
+/// An error returned when parsing a [`HardwareAddr`] fails.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HardwareAddrParseError(());
+
+impl fmt::Display for HardwareAddrParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid hardware address syntax")
+    }
+}
+
+impl Error for HardwareAddrParseError {}
+
 /// Details about the address of an interface on this host.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum IfAddr {
@@ -78,6 +437,70 @@ impl IfAddr {
         }
     }
 
+    /// Get the scope of this address.
+    pub fn scope(&self) -> Scope {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.scope,
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.scope,
+        }
+    }
+
+    /// Check whether this is a private IPv4 address (RFC 1918).
+    pub fn is_private(&self) -> bool {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.is_private(),
+            IfAddr::V6(_) => false,
+        }
+    }
+
+    /// Check whether this is an IPv6 unique local address (`fc00::/7`).
+    pub fn is_unique_local(&self) -> bool {
+        match *self {
+            IfAddr::V4(_) => false,
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.is_unique_local(),
+        }
+    }
+
+    /// Check whether this is an IPv4 shared address space (carrier-grade
+    /// NAT) address (`100.64.0.0/10`).
+    pub fn is_shared(&self) -> bool {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.is_shared(),
+            IfAddr::V6(_) => false,
+        }
+    }
+
+    /// Check whether this address is reserved for documentation.
+    pub fn is_documentation(&self) -> bool {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.is_documentation(),
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.is_documentation(),
+        }
+    }
+
+    /// Check whether this address appears to be globally reachable, i.e. it
+    /// is not in any of the special-purpose ranges of the IANA registries.
+    pub fn is_global(&self) -> bool {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.is_global(),
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.is_global(),
+        }
+    }
+
+    /// Get the flags describing the state of this address.
+    pub fn flags(&self) -> AddressFlags {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.flags,
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.flags,
+        }
+    }
+
+    /// Check whether this address is usable as a source address, i.e. it is
+    /// not tentative, deprecated or a duplicate.
+    pub fn is_preferred(&self) -> bool {
+        self.flags().is_preferred()
+    }
+
     /// Get the IP address of this interface address.
     pub fn ip(&self) -> IpAddr {
         match *self {
@@ -87,8 +510,218 @@ impl IfAddr {
     }
 }
 
-/// Details about the ipv4 address of an interface on this host.
+bitflags::bitflags! {
+    /// The state of an interface address, as reported by the OS.
+    ///
+    /// On Linux these mirror the kernel's `IFA_F_*` flags. On Windows they
+    /// are derived from the address's duplicate address detection state and
+    /// origin. Other platforms do not report them, and the flags are empty.
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+    pub struct AddressFlags: u32 {
+        /// Duplicate address detection has not yet completed.
+        const TENTATIVE = 1 << 0;
+        /// The address is usable while duplicate address detection is
+        /// still in progress (RFC 4429).
+        const OPTIMISTIC = 1 << 1;
+        /// The preferred lifetime of the address has expired, so it should
+        /// not be used for new connections.
+        const DEPRECATED = 1 << 2;
+        /// The address is a temporary IPv6 privacy address (RFC 8981).
+        const TEMPORARY = 1 << 3;
+        /// Duplicate address detection failed, so the address is unusable.
+        const DADFAILED = 1 << 4;
+        /// The address was configured statically rather than learned, and
+        /// does not expire.
+        const PERMANENT = 1 << 5;
+        /// No prefix route is created for the address.
+        const NOPREFIXROUTE = 1 << 6;
+        /// The address is a secondary IPv4 address on its subnet.
+        const SECONDARY = 1 << 7;
+    }
+}
+
+impl AddressFlags {
+    /// Check whether the address is in the preferred state, i.e. it is not
+    /// tentative, deprecated or a duplicate.
+    pub fn is_preferred(&self) -> bool {
+        !self.intersects(
+            AddressFlags::TENTATIVE | AddressFlags::DEPRECATED | AddressFlags::DADFAILED,
+        )
+    }
+}
+
+/// The scope of an interface address: the part of the network within which
+/// it is valid and unique.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Scope {
+    /// Valid only on this host, e.g. loopback addresses.
+    Host,
+    /// Valid only on the attached link, e.g. `169.254.0.0/16` and `fe80::/10`.
+    Link,
+    /// Valid only within a site, e.g. deprecated IPv6 site-local addresses.
+    Site,
+    /// Valid everywhere. Note that this includes private and unique local
+    /// addresses, which are not link or host specific; use the
+    /// classification helpers such as [`IfAddr::is_global`] to tell them
+    /// apart.
+    Global,
+}
+
+impl Scope {
+    /// Derive the scope of an IPv4 address from the address itself.
+    fn of_ipv4(ip: &Ipv4Addr) -> Self {
+        if ip.is_loopback() {
+            Scope::Host
+        } else if ip.is_link_local() {
+            Scope::Link
+        } else {
+            Scope::Global
+        }
+    }
+
+    /// Derive the scope of an IPv6 address from the address itself.
+    fn of_ipv6(ip: &Ipv6Addr) -> Self {
+        let segments = ip.segments();
+        if ip.is_loopback() {
+            Scope::Host
+        } else if (segments[0] & 0xffc0) == 0xfe80 {
+            Scope::Link
+        } else if (segments[0] & 0xffc0) == 0xfec0 {
+            Scope::Site
+        } else {
+            Scope::Global
+        }
+    }
+}
+
+/// The remaining lifetime of an interface address.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Lifetime {
+    /// The address does not expire.
+    Forever,
+    /// The address expires after this duration.
+    Finite(Duration),
+}
+
+impl Lifetime {
+    /// Convert a lifetime in seconds, where `u32::MAX` means forever, as it
+    /// does on both Linux and Windows.
+    #[cfg_attr(
+        not(any(windows, target_os = "linux", target_os = "android")),
+        allow(dead_code)
+    )]
+    fn from_secs(secs: u32) -> Self {
+        if secs == u32::MAX {
+            Lifetime::Forever
+        } else {
+            Lifetime::Finite(Duration::from_secs(secs.into()))
+        }
+    }
+}
+
+/// Options controlling which addresses [`get_if_addrs_with`] returns.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct GetIfAddrsOptions {
+    /// Skip addresses that are not in the preferred state: tentative,
+    /// deprecated or duplicate addresses. See [`AddressFlags::is_preferred`].
+    ///
+    /// Defaults to `false`.
+    pub preferred_only: bool,
+    /// Which link-local addresses (`169.254.0.0/16` and `fe80::`) to return.
+    ///
+    /// Defaults to [`LinkLocalPolicy::Include`].
+    pub link_local: LinkLocalPolicy,
+}
+
+impl Default for GetIfAddrsOptions {
+    fn default() -> Self {
+        Self {
+            preferred_only: false,
+            link_local: LinkLocalPolicy::Include,
+        }
+    }
+}
+
+impl GetIfAddrsOptions {
+    /// The options [`get_if_addrs`] has always used, which differ between
+    /// platforms and on the deprecated `link-local` feature.
+    fn legacy() -> Self {
+        let link_local = if cfg!(feature = "link-local") {
+            LinkLocalPolicy::Include
+        } else if cfg!(windows) {
+            LinkLocalPolicy::Exclude
+        } else {
+            LinkLocalPolicy::ExcludeIpv6
+        };
+        Self {
+            preferred_only: cfg!(windows),
+            link_local,
+        }
+    }
+
+    fn includes(&self, addr: &IfAddr) -> bool {
+        (!self.preferred_only || addr.is_preferred()) && self.link_local.includes(addr)
+    }
+}
+
+/// Which link-local addresses [`get_if_addrs_with`] returns.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LinkLocalPolicy {
+    /// Return all link-local addresses.
+    Include,
+    /// Skip both IPv4 (`169.254.0.0/16`) and IPv6 (`fe80::`) link-local
+    /// addresses.
+    Exclude,
+    /// Skip IPv6 link-local addresses only.
+    ExcludeIpv6,
+}
+
+impl LinkLocalPolicy {
+    fn includes(self, addr: &IfAddr) -> bool {
+        match (self, addr) {
+            (LinkLocalPolicy::Include, _) => true,
+            (LinkLocalPolicy::Exclude, _) => !addr.is_link_local(),
+            (LinkLocalPolicy::ExcludeIpv6, IfAddr::V4(_)) => true,
+            (LinkLocalPolicy::ExcludeIpv6, IfAddr::V6(v6)) => !v6.is_link_local(),
+        }
+    }
+}
+
+/// The destination of an interface address: the broadcast address of its
+/// subnet, or the remote end of a point-to-point link such as a tunnel or PPP
+/// connection.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum IfDestination<A> {
+    /// The broadcast address of the interface.
+    Broadcast(A),
+    /// The address of the remote peer of a point-to-point interface.
+    Peer(A),
+    /// The interface has neither a broadcast nor a peer address.
+    None,
+}
+
+impl<A: Copy> IfDestination<A> {
+    /// Get the broadcast address, if this is one.
+    pub fn broadcast(&self) -> Option<A> {
+        match *self {
+            IfDestination::Broadcast(addr) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// Get the peer address, if this is one.
+    pub fn peer(&self) -> Option<A> {
+        match *self {
+            IfDestination::Peer(addr) => Some(addr),
+            _ => None,
+        }
+    }
+}
+
+/// Details about the ipv4 address of an interface on this host.
+///
+/// Addresses are compared and hashed without their lifetimes and timestamps.
+#[derive(Debug, Clone)]
 pub struct Ifv4Addr {
     /// The IP address of the interface.
     pub ip: Ipv4Addr,
@@ -96,11 +729,64 @@ pub struct Ifv4Addr {
     pub netmask: Ipv4Addr,
     /// The CIDR prefix of the interface.
     pub prefixlen: u8,
-    /// The broadcast address of the interface.
-    pub broadcast: Option<Ipv4Addr>,
+    /// The broadcast or point-to-point peer address of the interface.
+    pub destination: IfDestination<Ipv4Addr>,
+    /// The scope of the address. On Linux this is the scope reported by the
+    /// kernel, elsewhere it is derived from the address.
+    pub scope: Scope,
+    /// The state of the address, where the OS reports it.
+    pub flags: AddressFlags,
+    /// How long the address remains preferred before it becomes
+    /// deprecated, where the OS reports it.
+    pub preferred_lifetime: Option<Lifetime>,
+    /// How long the address remains valid before it is removed, where the
+    /// OS reports it.
+    pub valid_lifetime: Option<Lifetime>,
+    /// (Linux only) When the address was added, as the time since boot.
+    pub created: Option<Duration>,
+    /// (Linux only) When the address was last updated, e.g. refreshed by a
+    /// router advertisement, as the time since boot.
+    pub updated: Option<Duration>,
+}
+
+// The lifetimes and timestamps are left out, as they change from one query to
+// the next while the address itself stays the same.
+impl PartialEq for Ifv4Addr {
+    fn eq(&self, other: &Self) -> bool {
+        self.ip == other.ip
+            && self.netmask == other.netmask
+            && self.prefixlen == other.prefixlen
+            && self.destination == other.destination
+            && self.scope == other.scope
+            && self.flags == other.flags
+    }
+}
+
+impl Eq for Ifv4Addr {}
+
+impl Hash for Ifv4Addr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ip.hash(state);
+        self.netmask.hash(state);
+        self.prefixlen.hash(state);
+        self.destination.hash(state);
+        self.scope.hash(state);
+        self.flags.hash(state);
+    }
 }
 
 impl Ifv4Addr {
+    /// Get the broadcast address of the interface, if it has one.
+    pub fn broadcast(&self) -> Option<Ipv4Addr> {
+        self.destination.broadcast()
+    }
+
+    /// Get the address of the remote peer, if this is a point-to-point
+    /// interface.
+    pub fn peer(&self) -> Option<Ipv4Addr> {
+        self.destination.peer()
+    }
+
     /// Check whether this is a loopback address.
     pub fn is_loopback(&self) -> bool {
         self.ip.octets()[0] == 127
@@ -110,10 +796,49 @@ impl Ifv4Addr {
     pub fn is_link_local(&self) -> bool {
         self.ip.is_link_local()
     }
+
+    /// Check whether this is a private address (RFC 1918).
+    pub fn is_private(&self) -> bool {
+        self.ip.is_private()
+    }
+
+    /// Check whether this is a shared address space (carrier-grade NAT)
+    /// address (`100.64.0.0/10`).
+    pub fn is_shared(&self) -> bool {
+        let octets = self.ip.octets();
+        octets[0] == 100 && (octets[1] & 0xc0) == 64
+    }
+
+    /// Check whether this address is reserved for documentation
+    /// (`192.0.2.0/24`, `198.51.100.0/24` and `203.0.113.0/24`).
+    pub fn is_documentation(&self) -> bool {
+        self.ip.is_documentation()
+    }
+
+    /// Check whether this address appears to be globally reachable, i.e. it
+    /// is not in any of the special-purpose ranges of the IANA registry.
+    pub fn is_global(&self) -> bool {
+        let octets = self.ip.octets();
+        !(octets[0] == 0
+            || self.is_private()
+            || self.is_shared()
+            || self.ip.is_loopback()
+            || self.is_link_local()
+            // IETF protocol assignments, except the globally reachable
+            // PCP and TURN anycast addresses
+            || (octets[..3] == [192, 0, 0] && octets[3] != 9 && octets[3] != 10)
+            || self.is_documentation()
+            // benchmarking
+            || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+            // reserved, including broadcast
+            || octets[0] >= 240)
+    }
 }
 
 /// Details about the ipv6 address of an interface on this host.
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+///
+/// Addresses are compared and hashed without their lifetimes and timestamps.
+#[derive(Debug, Clone)]
 pub struct Ifv6Addr {
     /// The IP address of the interface.
     pub ip: Ipv6Addr,
@@ -121,11 +846,68 @@ pub struct Ifv6Addr {
     pub netmask: Ipv6Addr,
     /// The CIDR prefix of the interface.
     pub prefixlen: u8,
-    /// The broadcast address of the interface.
-    pub broadcast: Option<Ipv6Addr>,
+    /// The point-to-point peer address of the interface. IPv6 has no
+    /// broadcast, so this is never [`IfDestination::Broadcast`].
+    pub destination: IfDestination<Ipv6Addr>,
+    /// The scope of the address. On Linux this is the scope reported by the
+    /// kernel, elsewhere it is derived from the address.
+    pub scope: Scope,
+    /// The state of the address, where the OS reports it.
+    pub flags: AddressFlags,
+    /// How long the address remains preferred before it becomes
+    /// deprecated, where the OS reports it.
+    pub preferred_lifetime: Option<Lifetime>,
+    /// How long the address remains valid before it is removed, where the
+    /// OS reports it.
+    pub valid_lifetime: Option<Lifetime>,
+    /// (Linux only) When the address was added, as the time since boot.
+    pub created: Option<Duration>,
+    /// (Linux only) When the address was last updated, e.g. refreshed by a
+    /// router advertisement, as the time since boot.
+    pub updated: Option<Duration>,
+}
+
+// The lifetimes and timestamps are left out, as they change from one query to
+// the next while the address itself stays the same.
+impl PartialEq for Ifv6Addr {
+    fn eq(&self, other: &Self) -> bool {
+        self.ip == other.ip
+            && self.netmask == other.netmask
+            && self.prefixlen == other.prefixlen
+            && self.destination == other.destination
+            && self.scope == other.scope
+            && self.flags == other.flags
+    }
+}
+
+impl Eq for Ifv6Addr {}
+
+impl Hash for Ifv6Addr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ip.hash(state);
+        self.netmask.hash(state);
+        self.prefixlen.hash(state);
+        self.destination.hash(state);
+        self.scope.hash(state);
+        self.flags.hash(state);
+    }
 }
 
 impl Ifv6Addr {
+    /// Always `None`, as IPv6 has no broadcast addresses. Provided for code
+    /// written against the former `broadcast` field, which held the peer
+    /// of point-to-point interfaces.
+    #[deprecated(since = "0.14.0", note = "use `peer` or `destination` instead")]
+    pub fn broadcast(&self) -> Option<Ipv6Addr> {
+        None
+    }
+
+    /// Get the address of the remote peer, if this is a point-to-point
+    /// interface.
+    pub fn peer(&self) -> Option<Ipv6Addr> {
+        self.destination.peer()
+    }
+
     /// Check whether this is a loopback address.
     pub fn is_loopback(&self) -> bool {
         self.ip.segments() == [0, 0, 0, 0, 0, 0, 0, 1]
@@ -137,26 +919,93 @@ impl Ifv6Addr {
 
         bytes[0] == 0xfe && bytes[1] == 0x80
     }
+
+    /// Check whether this is a unique local address (`fc00::/7`).
+    pub fn is_unique_local(&self) -> bool {
+        (self.ip.segments()[0] & 0xfe00) == 0xfc00
+    }
+
+    /// Check whether this address is reserved for documentation
+    /// (`2001:db8::/32` and `3fff::/20`).
+    pub fn is_documentation(&self) -> bool {
+        let segments = self.ip.segments();
+        (segments[0] == 0x2001 && segments[1] == 0xdb8) || (segments[0] & 0xfff0) == 0x3ff0
+    }
+
+    /// Check whether this address appears to be globally reachable, i.e. it
+    /// is not in any of the special-purpose ranges of the IANA registry.
+    pub fn is_global(&self) -> bool {
+        let segments = self.ip.segments();
+        !(self.ip.is_unspecified()
+            || self.is_loopback()
+            // IPv4-mapped
+            || segments[..6] == [0, 0, 0, 0, 0, 0xffff]
+            // IPv4-IPv6 translation for local use
+            || segments[..3] == [0x64, 0xff9b, 1]
+            // discard-only
+            || segments[..4] == [0x100, 0, 0, 0]
+            // IETF protocol assignments, except the globally reachable AMT,
+            // AS112-v6 and ORCHIDv2 ranges
+            || (segments[0] == 0x2001
+                && segments[1] < 0x200
+                && !(segments[1] == 3
+                    || (segments[1] == 4 && segments[2] == 0x112)
+                    || (0x20..=0x2f).contains(&segments[1])))
+            || self.is_documentation()
+            || self.is_unique_local()
+            // link-local and deprecated site-local
+            || (segments[0] & 0xffc0) == 0xfe80
+            || (segments[0] & 0xffc0) == 0xfec0)
+    }
 }
 
 #[cfg(not(windows))]
 mod getifaddrs_posix {
     use libc::if_nametoindex;
 
-    use super::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+    use super::{
+        AddressFlags, HardwareAddr, IfAddr, IfDestination, Ifv4Addr, Ifv6Addr, Interface,
+        InterfaceFlags, NetworkInterface, OperState, Scope,
+    };
     use crate::posix::{self as ifaddrs, IfAddrs};
     use crate::sockaddr;
+    use std::collections::HashMap;
     use std::ffi::CStr;
     use std::io;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
+    /// Tell whether the destination address `getifaddrs` reports along with
+    /// `ip` is the peer of a point-to-point interface or a broadcast address.
+    pub fn classify_destination<A: PartialEq>(
+        flags: InterfaceFlags,
+        ip: A,
+        destination: A,
+    ) -> IfDestination<A> {
+        // A peer equal to the local address means none is configured
+        if flags.is_point_to_point() && destination != ip {
+            IfDestination::Peer(destination)
+        } else if flags.supports_broadcast() {
+            IfDestination::Broadcast(destination)
+        } else {
+            IfDestination::None
+        }
+    }
+
     /// Return a vector of IP details for all the valid interfaces on this host.
     #[allow(unsafe_code)]
     pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
         let mut ret = Vec::<Interface>::new();
         let ifaddrs = IfAddrs::new()?;
 
+        // Link-layer entries (AF_PACKET/AF_LINK) carry the hardware address of
+        // each interface, and are listed separately from its IP addresses.
+        let hw_addrs: HashMap<u32, HardwareAddr> = ifaddrs
+            .iter()
+            .filter_map(|ifaddr| sockaddr::to_hwaddr(ifaddr.ifa_addr))
+            .collect();
+
         for ifaddr in ifaddrs.iter() {
+            let flags = ifaddrs::interface_flags(&ifaddr);
             let addr = match sockaddr::to_ipaddr(ifaddr.ifa_addr) {
                 None => continue,
                 Some(IpAddr::V4(ipv4_addr)) => {
@@ -164,13 +1013,11 @@ mod getifaddrs_posix {
                         Some(IpAddr::V4(netmask)) => netmask,
                         _ => Ipv4Addr::new(0, 0, 0, 0),
                     };
-                    let broadcast = if (ifaddr.ifa_flags & 2) != 0 {
-                        match ifaddrs::do_broadcast(&ifaddr) {
-                            Some(IpAddr::V4(broadcast)) => Some(broadcast),
-                            _ => None,
+                    let destination = match ifaddrs::do_destination(&ifaddr) {
+                        Some(IpAddr::V4(destination)) => {
+                            classify_destination(flags, ipv4_addr, destination)
                         }
-                    } else {
-                        None
+                        _ => IfDestination::None,
                     };
                     let prefixlen = if cfg!(target_endian = "little") {
                         u32::from_le_bytes(netmask.octets()).count_ones() as u8
@@ -181,7 +1028,13 @@ mod getifaddrs_posix {
                         ip: ipv4_addr,
                         netmask,
                         prefixlen,
-                        broadcast,
+                        destination,
+                        scope: Scope::of_ipv4(&ipv4_addr),
+                        flags: AddressFlags::empty(),
+                        preferred_lifetime: None,
+                        valid_lifetime: None,
+                        created: None,
+                        updated: None,
                     })
                 }
                 Some(IpAddr::V6(ipv6_addr)) => {
@@ -189,13 +1042,15 @@ mod getifaddrs_posix {
                         Some(IpAddr::V6(netmask)) => netmask,
                         _ => Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
                     };
-                    let broadcast = if (ifaddr.ifa_flags & 2) != 0 {
-                        match ifaddrs::do_broadcast(&ifaddr) {
-                            Some(IpAddr::V6(broadcast)) => Some(broadcast),
-                            _ => None,
+                    // IPv6 has no broadcast addresses
+                    let destination = match ifaddrs::do_destination(&ifaddr) {
+                        Some(IpAddr::V6(destination)) => {
+                            match classify_destination(flags, ipv6_addr, destination) {
+                                IfDestination::Peer(peer) => IfDestination::Peer(peer),
+                                _ => IfDestination::None,
+                            }
                         }
-                    } else {
-                        None
+                        _ => IfDestination::None,
                     };
                     let prefixlen = if cfg!(target_endian = "little") {
                         u128::from_le_bytes(netmask.octets()).count_ones() as u8
@@ -206,7 +1061,13 @@ mod getifaddrs_posix {
                         ip: ipv6_addr,
                         netmask,
                         prefixlen,
-                        broadcast,
+                        destination,
+                        scope: Scope::of_ipv6(&ipv6_addr),
+                        flags: AddressFlags::empty(),
+                        preferred_lifetime: None,
+                        valid_lifetime: None,
+                        created: None,
+                        updated: None,
                     })
                 }
             };
@@ -226,43 +1087,211 @@ mod getifaddrs_posix {
                     Some(index)
                 }
             };
-            ret.push(Interface { name, addr, index });
+            let hw_addr = index.and_then(|index| hw_addrs.get(&index).copied());
+            ret.push(Interface {
+                name,
+                addr,
+                index,
+                flags,
+                hw_addr,
+            });
         }
 
         Ok(ret)
     }
-}
 
-/// Get a list of all the network interfaces on this machine along with their IP info.
-#[cfg(not(windows))]
-pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-    getifaddrs_posix::get_if_addrs()
+    /// Return all the interfaces on this host, without their addresses.
+    /// Interfaces without an IP address are only included where `getifaddrs`
+    /// lists link-layer entries for them, as on Linux and the BSDs.
+    #[allow(unsafe_code)]
+    pub fn get_links() -> io::Result<Vec<NetworkInterface>> {
+        let mut ret = Vec::<NetworkInterface>::new();
+        let ifaddrs = IfAddrs::new()?;
+
+        for ifaddr in ifaddrs.iter() {
+            let index = unsafe { if_nametoindex(ifaddr.ifa_name) };
+            if index == 0 {
+                // The interface has been removed since `getifaddrs` was called
+                continue;
+            }
+            let c_name = unsafe { CStr::from_ptr(ifaddr.ifa_name) };
+            let name = c_name.to_string_lossy().into_owned();
+            let link = match ret.iter_mut().position(|link| link.index == index) {
+                Some(position) => &mut ret[position],
+                None => {
+                    ret.push(NetworkInterface {
+                        name: name.clone(),
+                        index,
+                        flags: ifaddrs::interface_flags(&ifaddr),
+                        hw_addr: None,
+                        addrs: Vec::new(),
+                        mtu: ifaddrs::mtu(c_name),
+                        tx_queue_len: None,
+                        link_type: None,
+                        oper_state: OperState::Unknown,
+                        master: None,
+                    });
+                    ret.last_mut().unwrap()
+                }
+            };
+            // IPv4 aliases such as "eth0:1" share the index of their
+            // interface, so prefer the name of the non-IP entries.
+            if sockaddr::to_ipaddr(ifaddr.ifa_addr).is_none() {
+                link.name = name;
+            }
+            if let Some((_, hw_addr)) = sockaddr::to_hwaddr(ifaddr.ifa_addr) {
+                link.hw_addr = Some(hw_addr);
+            }
+            if let Some(link_type) = sockaddr::to_link_type(ifaddr.ifa_addr) {
+                link.link_type = Some(link_type);
+            }
+        }
+
+        Ok(ret)
+    }
 }
 
-#[cfg(windows)]
-mod getifaddrs_windows {
-    use super::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
-    use crate::sockaddr;
-    use crate::windows::IfAddrs;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod getifaddrs_netlink {
+    use super::{IfAddr, IfDestination, Ifv4Addr, Ifv6Addr, Interface, NetworkInterface};
+    use crate::netlink::{AddrMessage, LinkMessage, RouteSocket};
+    use crate::posix;
+    use libc::c_int;
+    use std::collections::HashMap;
     use std::io;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-    use windows_sys::Win32::Networking::WinSock::IpDadStatePreferred;
 
-    /// Return a vector of IP details for all the valid interfaces on this host.
+    /// Return a vector of IP details for all the valid interfaces on this
+    /// host, read directly from the kernel's rtnetlink link and address
+    /// tables.
     pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-        let mut ret = Vec::<Interface>::new();
-        let ifaddrs = IfAddrs::new()?;
+        let mut socket = RouteSocket::new()?;
+        let links: HashMap<u32, _> = socket
+            .links()?
+            .into_iter()
+            .map(|link| (link.index, link))
+            .collect();
+
+        Ok(socket
+            .addrs()?
+            .iter()
+            .filter_map(|msg| interface_from(links.get(&msg.index)?, msg))
+            .collect())
+    }
+
+    /// Build the interface an address message describes, given the link it
+    /// belongs to.
+    pub fn interface_from(link: &LinkMessage, msg: &AddrMessage) -> Option<Interface> {
+        let flags = posix::flags_from_raw(link.flags as c_int);
+
+        let addr = match (msg.ip(), msg.peer(), msg.broadcast) {
+            (Some(IpAddr::V4(ip)), peer, broadcast) => {
+                let prefixlen = msg.prefixlen.min(32);
+                let netmask =
+                    Ipv4Addr::from(u32::MAX.checked_shl(32 - prefixlen as u32).unwrap_or(0));
+                let destination = match (peer, broadcast) {
+                    (Some(IpAddr::V4(peer)), _) => IfDestination::Peer(peer),
+                    (_, Some(IpAddr::V4(broadcast))) => IfDestination::Broadcast(broadcast),
+                    _ => IfDestination::None,
+                };
+                IfAddr::V4(Ifv4Addr {
+                    ip,
+                    netmask,
+                    prefixlen,
+                    destination,
+                    scope: msg.scope(),
+                    flags: msg.address_flags(),
+                    preferred_lifetime: msg.preferred_lifetime(),
+                    valid_lifetime: msg.valid_lifetime(),
+                    created: msg.created(),
+                    updated: msg.updated(),
+                })
+            }
+            (Some(IpAddr::V6(ip)), peer, _) => {
+                let prefixlen = msg.prefixlen.min(128);
+                let netmask =
+                    Ipv6Addr::from(u128::MAX.checked_shl(128 - prefixlen as u32).unwrap_or(0));
+                let destination = match peer {
+                    Some(IpAddr::V6(peer)) => IfDestination::Peer(peer),
+                    _ => IfDestination::None,
+                };
+                IfAddr::V6(Ifv6Addr {
+                    ip,
+                    netmask,
+                    prefixlen,
+                    destination,
+                    scope: msg.scope(),
+                    flags: msg.address_flags(),
+                    preferred_lifetime: msg.preferred_lifetime(),
+                    valid_lifetime: msg.valid_lifetime(),
+                    created: msg.created(),
+                    updated: msg.updated(),
+                })
+            }
+            (None, _, _) => return None,
+        };
+
+        // Like `getifaddrs`, name IPv4 addresses after their label, which is
+        // how aliases such as "eth0:1" are reported.
+        let name = match (&addr, &msg.label) {
+            (IfAddr::V4(_), Some(label)) => label.clone(),
+            _ => link.name.clone(),
+        };
+        Some(Interface {
+            name,
+            addr,
+            index: Some(msg.index),
+            flags,
+            hw_addr: link.hw_addr,
+        })
+    }
+
+    /// Describe a link, without its addresses.
+    pub fn link_from(link: &LinkMessage) -> NetworkInterface {
+        NetworkInterface {
+            mtu: link.mtu,
+            tx_queue_len: link.tx_queue_len,
+            link_type: Some(link.link_type()),
+            oper_state: link.oper_state(),
+            master: link.master,
+            name: link.name.clone(),
+            index: link.index,
+            flags: posix::flags_from_raw(link.flags as c_int),
+            hw_addr: link.hw_addr,
+            addrs: Vec::new(),
+        }
+    }
+
+    /// Return all the links in the kernel's rtnetlink link table, without
+    /// their addresses.
+    pub fn get_links() -> io::Result<Vec<NetworkInterface>> {
+        Ok(RouteSocket::new()?.links()?.iter().map(link_from).collect())
+    }
+}
+
+#[cfg(windows)]
+mod getifaddrs_windows {
+    use super::{
+        IfAddr, IfDestination, Ifv4Addr, Ifv6Addr, Interface, Lifetime, NetworkInterface, Scope,
+    };
+    use crate::sockaddr;
+    use crate::windows::{address_flags, IfAddrs};
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    /// Return a vector of IP details for all the valid interfaces on this host.
+    pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
+        let mut ret = Vec::<Interface>::new();
+        let ifaddrs = IfAddrs::new()?;
 
         for ifaddr in ifaddrs.iter() {
             for addr in ifaddr.unicast_addresses() {
-                if addr.DadState != IpDadStatePreferred {
-                    continue;
-                }
+                let flags = address_flags(addr);
                 let addr = match sockaddr::to_ipaddr(addr.Address.lpSockaddr) {
                     None => continue,
                     Some(IpAddr::V4(ipv4_addr)) => {
                         let mut item_netmask = Ipv4Addr::new(0, 0, 0, 0);
-                        let mut item_broadcast = None;
+                        let mut item_destination = IfDestination::None;
                         let item_prefix = addr.OnLinkPrefixLength;
 
                         // Search prefixes for a prefix matching addr
@@ -297,7 +1326,7 @@ mod getifaddrs_windows {
                                     for n in 0..4 {
                                         broadcast[n] |= !netmask[n];
                                     }
-                                    item_broadcast = Some(Ipv4Addr::new(
+                                    item_destination = IfDestination::Broadcast(Ipv4Addr::new(
                                         broadcast[0],
                                         broadcast[1],
                                         broadcast[2],
@@ -312,7 +1341,13 @@ mod getifaddrs_windows {
                             ip: ipv4_addr,
                             netmask: item_netmask,
                             prefixlen: item_prefix,
-                            broadcast: item_broadcast,
+                            destination: item_destination,
+                            scope: Scope::of_ipv4(&ipv4_addr),
+                            flags,
+                            preferred_lifetime: Some(Lifetime::from_secs(addr.PreferredLifetime)),
+                            valid_lifetime: Some(Lifetime::from_secs(addr.ValidLifetime)),
+                            created: None,
+                            updated: None,
                         })
                     }
                     Some(IpAddr::V6(ipv6_addr)) => {
@@ -358,7 +1393,13 @@ mod getifaddrs_windows {
                             ip: ipv6_addr,
                             netmask: item_netmask,
                             prefixlen: item_prefix,
-                            broadcast: None,
+                            destination: IfDestination::None,
+                            scope: Scope::of_ipv6(&ipv6_addr),
+                            flags,
+                            preferred_lifetime: Some(Lifetime::from_secs(addr.PreferredLifetime)),
+                            valid_lifetime: Some(Lifetime::from_secs(addr.ValidLifetime)),
+                            created: None,
+                            updated: None,
                         })
                     }
                 };
@@ -371,6 +1412,8 @@ mod getifaddrs_windows {
                     name: ifaddr.name(),
                     addr,
                     index,
+                    flags: ifaddr.flags(),
+                    hw_addr: ifaddr.hw_addr(),
                     adapter_name: ifaddr.adapter_name(),
                 });
             }
@@ -378,25 +1421,380 @@ mod getifaddrs_windows {
 
         Ok(ret)
     }
+
+    /// Return all the adapters on this host, without their addresses.
+    pub fn get_links() -> io::Result<Vec<NetworkInterface>> {
+        let ifaddrs = IfAddrs::new()?;
+
+        Ok(ifaddrs
+            .iter()
+            .filter_map(|ifaddr| {
+                Some(NetworkInterface {
+                    name: ifaddr.name(),
+                    index: ifaddr.ipv4_index().or_else(|| ifaddr.ipv6_index())?,
+                    flags: ifaddr.flags(),
+                    hw_addr: ifaddr.hw_addr(),
+                    addrs: Vec::new(),
+                    mtu: Some(ifaddr.mtu()),
+                    tx_queue_len: None,
+                    link_type: Some(ifaddr.link_type()),
+                    oper_state: ifaddr.oper_state(),
+                    master: None,
+                    adapter_name: ifaddr.adapter_name(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_all_if_addrs() -> io::Result<Vec<Interface>> {
+    // Some sandboxes, and Android for unprivileged apps, restrict rtnetlink
+    // dumps, while still allowing `getifaddrs`.
+    match getifaddrs_netlink::get_if_addrs() {
+        Err(e) if netlink::is_unavailable(&e) => getifaddrs_posix::get_if_addrs(),
+        result => result,
+    }
+}
+
+#[cfg(all(not(windows), not(any(target_os = "linux", target_os = "android"))))]
+fn get_all_if_addrs() -> io::Result<Vec<Interface>> {
+    getifaddrs_posix::get_if_addrs()
 }
 
-/// Get a list of all the network interfaces on this machine along with their IP info.
 #[cfg(windows)]
-pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
+fn get_all_if_addrs() -> io::Result<Vec<Interface>> {
     getifaddrs_windows::get_if_addrs()
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_all_links() -> io::Result<Vec<NetworkInterface>> {
+    match getifaddrs_netlink::get_links() {
+        Err(e) if netlink::is_unavailable(&e) => getifaddrs_posix::get_links(),
+        result => result,
+    }
+}
+
+#[cfg(all(not(windows), not(any(target_os = "linux", target_os = "android"))))]
+fn get_all_links() -> io::Result<Vec<NetworkInterface>> {
+    getifaddrs_posix::get_links()
+}
+
+#[cfg(windows)]
+fn get_all_links() -> io::Result<Vec<NetworkInterface>> {
+    getifaddrs_windows::get_links()
+}
+
+/// Get a list of all the network interfaces on this machine along with their IP info.
+///
+/// For compatibility with previous versions, this skips non-preferred
+/// addresses on Windows, and link-local addresses (only IPv6 ones outside
+/// Windows) unless the deprecated `link-local` feature is enabled. Use
+/// [`get_if_addrs_with`] for the same addresses on every platform.
+pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
+    get_if_addrs_with(&GetIfAddrsOptions::legacy())
+}
+
+/// Get a list of the network interfaces on this machine along with their IP
+/// info, keeping only the addresses selected by `options`.
+///
+/// ```no_run
+/// let options = if_addrs::GetIfAddrsOptions {
+///     preferred_only: true,
+///     ..Default::default()
+/// };
+/// for iface in if_addrs::get_if_addrs_with(&options).unwrap() {
+///     println!("{:#?}", iface);
+/// }
+/// ```
+pub fn get_if_addrs_with(options: &GetIfAddrsOptions) -> io::Result<Vec<Interface>> {
+    let mut ifaces = get_all_if_addrs()?;
+    ifaces.retain(|interface| options.includes(&interface.addr));
+    Ok(ifaces)
+}
+
+/// Get the network interfaces on this machine, ordered by index, each with
+/// all of its IP addresses. Interfaces without an IP address, such as
+/// SocketCAN interfaces, bridge ports and interfaces that are down, are
+/// included.
+///
+/// ```no_run
+/// for iface in if_addrs::get_interfaces().unwrap() {
+///     println!("{} ({}): {:?}", iface.name, iface.index, iface.ips().collect::<Vec<_>>());
+/// }
+/// ```
+pub fn get_interfaces() -> io::Result<Vec<NetworkInterface>> {
+    get_interfaces_with(&GetIfAddrsOptions::default())
+}
+
+/// Get the network interfaces on this machine, ordered by index, keeping only
+/// the addresses selected by `options`. Interfaces are listed even if none of
+/// their addresses are selected.
+pub fn get_interfaces_with(options: &GetIfAddrsOptions) -> io::Result<Vec<NetworkInterface>> {
+    let mut links = get_all_links()?;
+    let mut positions: HashMap<LinkKey, usize> = links
+        .iter()
+        .enumerate()
+        .map(|(position, link)| (link.key(), position))
+        .collect();
+    for interface in get_all_if_addrs()? {
+        if !options.includes(&interface.addr) {
+            continue;
+        }
+        let key = match interface.link_key() {
+            Some(key) => key,
+            None => continue,
+        };
+        match positions.get(&key) {
+            Some(&position) => links[position].addrs.push(interface.addr),
+            // The interface appeared after the links were listed
+            None => {
+                if let Some(mut link) = NetworkInterface::from_interface(&interface) {
+                    link.addrs.push(interface.addr);
+                    positions.insert(key, links.len());
+                    links.push(link);
+                }
+            }
+        }
+    }
+    links.sort_by_key(|link| link.index);
+    Ok(links)
+}
+
+/// Get the network interface with the given name, if there is one.
+pub fn get_interface_by_name(name: &str) -> io::Result<Option<NetworkInterface>> {
+    Ok(get_interfaces()?.into_iter().find(|link| link.name == name))
+}
+
+/// Get the network interface with the given index, if there is one.
+pub fn get_interface_by_index(index: u32) -> io::Result<Option<NetworkInterface>> {
+    Ok(get_interfaces()?
+        .into_iter()
+        .find(|link| link.index == index))
+}
+
 #[cfg(not(any(target_os = "macos", target_os = "ios")))]
 mod if_change_notifier {
-    use super::Interface;
+    use super::{Interface, NetworkInterface};
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use crate::getifaddrs_netlink::{interface_from, link_from};
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use crate::netlink::{self, Change, LinkMessage, RouteMessage, RouteSocket};
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use crate::posix;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use crate::route::{self, DefaultGateway, Route};
+    use std::collections::HashMap;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
     use std::collections::HashSet;
     use std::io;
+    use std::net::IpAddr;
     use std::time::{Duration, Instant};
 
     #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+    #[non_exhaustive]
     pub enum IfChangeType {
+        /// An address was added.
         Added(Interface),
+        /// An address was removed.
         Removed(Interface),
+        /// An address was replaced by one with the same IP on the same
+        /// interface, such as when its netmask, broadcast address or flags
+        /// change.
+        Modified {
+            /// The address before the change.
+            old: Interface,
+            /// The address after the change.
+            new: Interface,
+            /// The fields that differ between the two, ignoring the
+            /// address lifetimes.
+            changed: ChangedFields,
+        },
+        /// An interface was created, such as when a USB adapter is plugged
+        /// in or a VPN tunnel is set up.
+        ///
+        /// The interfaces of link-level changes are listed without their
+        /// addresses. On Linux, their addresses aren't reported as changed
+        /// along with them unless their name changes. Where the addresses can only be read with `getifaddrs`,
+        /// such as when rtnetlink is restricted on Linux, interfaces without
+        /// addresses aren't seen and no link events are reported.
+        LinkAdded(NetworkInterface),
+        /// An interface was removed. The removal of its addresses is reported
+        /// separately.
+        LinkRemoved(NetworkInterface),
+        /// An interface was brought up administratively. It may not have a
+        /// carrier yet, see [`NetworkInterface::is_running`].
+        LinkUp(NetworkInterface),
+        /// An interface was brought down administratively.
+        LinkDown(NetworkInterface),
+        /// An interface that stayed up gained or lost its carrier, such as
+        /// when a cable is plugged in or pulled out, or a WiFi network is
+        /// joined or left. [`NetworkInterface::is_running`] tells which.
+        CarrierChanged(NetworkInterface),
+        /// An interface was renamed.
+        Renamed {
+            /// The interface before the change.
+            old: NetworkInterface,
+            /// The interface after the change.
+            new: NetworkInterface,
+        },
+        /// The MTU of an interface changed.
+        MtuChanged {
+            /// The interface before the change.
+            old: NetworkInterface,
+            /// The interface after the change.
+            new: NetworkInterface,
+        },
+        /// (Linux/Android only) A default route was added.
+        ///
+        /// Route changes are only reported by notifiers built with
+        /// [`IfChangeNotifierBuilder::routes`].
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        DefaultRouteAdded(DefaultGateway),
+        /// (Linux/Android only) A default route was removed.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        DefaultRouteRemoved(DefaultGateway),
+        /// (Linux/Android only) A default route was replaced by one in the
+        /// same table with the same metric, such as when its gateway or
+        /// output interface changes.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        DefaultRouteChanged {
+            /// The route before the change.
+            old: DefaultGateway,
+            /// The route after the change.
+            new: DefaultGateway,
+        },
+        /// (Linux/Android only) The IPv4 or IPv6 default route that is
+        /// preferred, i.e. the one with the lowest metric in the main table,
+        /// now uses another gateway, output interface or source address.
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        PreferredDefaultRouteChanged {
+            /// The preferred route before the change, if there was one.
+            old: Option<DefaultGateway>,
+            /// The preferred route after the change, if there is one.
+            new: Option<DefaultGateway>,
+        },
+    }
+
+    /// Record the link-level changes between two states of an interface.
+    pub(crate) fn link_changes(
+        old: &NetworkInterface,
+        new: &NetworkInterface,
+        changes: &mut Vec<IfChangeType>,
+    ) {
+        if old.is_up() != new.is_up() {
+            changes.push(match new.is_up() {
+                true => IfChangeType::LinkUp(new.clone()),
+                false => IfChangeType::LinkDown(new.clone()),
+            });
+        } else if old.is_running() != new.is_running() {
+            changes.push(IfChangeType::CarrierChanged(new.clone()));
+        }
+        if old.name != new.name {
+            changes.push(IfChangeType::Renamed {
+                old: old.clone(),
+                new: new.clone(),
+            });
+        }
+        if old.mtu != new.mtu {
+            changes.push(IfChangeType::MtuChanged {
+                old: old.clone(),
+                new: new.clone(),
+            });
+        }
+    }
+
+    bitflags::bitflags! {
+        /// The fields of an [`Interface`] that differ between the old and new
+        /// values of an [`IfChangeType::Modified`] change.
+        #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+        pub struct ChangedFields: u32 {
+            /// The name of the interface, or the label of the IPv4 address.
+            const NAME = 1 << 0;
+            /// The netmask and prefix length of the address.
+            const NETMASK = 1 << 1;
+            /// The broadcast or peer address.
+            const DESTINATION = 1 << 2;
+            /// The scope of the address.
+            const SCOPE = 1 << 3;
+            /// The flags describing the state of the address.
+            const ADDRESS_FLAGS = 1 << 4;
+            /// The flags describing the state of the interface, such as
+            /// whether it is up.
+            const FLAGS = 1 << 5;
+            /// The hardware address of the interface.
+            const HW_ADDR = 1 << 6;
+        }
+    }
+
+    impl ChangedFields {
+        fn between(old: &Interface, new: &Interface) -> Self {
+            use super::IfAddr;
+
+            let mut changed = ChangedFields::empty();
+            changed.set(ChangedFields::NAME, old.name != new.name);
+            let (netmask, destination) = match (&old.addr, &new.addr) {
+                (IfAddr::V4(old), IfAddr::V4(new)) => (
+                    (old.netmask, old.prefixlen) != (new.netmask, new.prefixlen),
+                    old.destination != new.destination,
+                ),
+                (IfAddr::V6(old), IfAddr::V6(new)) => (
+                    (old.netmask, old.prefixlen) != (new.netmask, new.prefixlen),
+                    old.destination != new.destination,
+                ),
+                _ => (true, true),
+            };
+            changed.set(ChangedFields::NETMASK, netmask);
+            changed.set(ChangedFields::DESTINATION, destination);
+            changed.set(ChangedFields::SCOPE, old.addr.scope() != new.addr.scope());
+            changed.set(
+                ChangedFields::ADDRESS_FLAGS,
+                old.addr.flags() != new.addr.flags(),
+            );
+            changed.set(ChangedFields::FLAGS, old.flags != new.flags);
+            changed.set(ChangedFields::HW_ADDR, old.hw_addr != new.hw_addr);
+            changed
+        }
+    }
+
+    /// Report the removal of an address followed by the addition of the same
+    /// IP on the same interface as a single modification. Pairs which turn
+    /// out to be identical are dropped.
+    pub(crate) fn pair_modified(changes: Vec<IfChangeType>) -> Vec<IfChangeType> {
+        let mut paired: Vec<Option<IfChangeType>> = Vec::with_capacity(changes.len());
+        // The positions of the removals not paired yet, by interface and IP
+        let mut removed: HashMap<(Option<u32>, IpAddr), Vec<usize>> = HashMap::new();
+        for change in changes {
+            let new = match change {
+                IfChangeType::Added(new) => new,
+                IfChangeType::Removed(old) => {
+                    let key = (old.index, old.ip());
+                    removed.entry(key).or_default().push(paired.len());
+                    paired.push(Some(IfChangeType::Removed(old)));
+                    continue;
+                }
+                change => {
+                    paired.push(Some(change));
+                    continue;
+                }
+            };
+            let i = match removed.get_mut(&(new.index, new.ip())) {
+                Some(positions) if !positions.is_empty() => positions.remove(0),
+                _ => {
+                    paired.push(Some(IfChangeType::Added(new)));
+                    continue;
+                }
+            };
+            let old = match paired[i].take() {
+                Some(IfChangeType::Removed(old)) => old,
+                _ => unreachable!("only removals are indexed"),
+            };
+            let changed = ChangedFields::between(&old, &new);
+            if !changed.is_empty() {
+                paired[i] = Some(IfChangeType::Modified { old, new, changed });
+            }
+        }
+        paired.into_iter().flatten().collect()
     }
 
     #[cfg(windows)]
@@ -404,196 +1802,1732 @@ mod if_change_notifier {
     #[cfg(not(windows))]
     type InternalIfChangeNotifier = crate::posix_not_mac::PosixIfChangeNotifier;
 
+    /// The size of the buffer notifications are received into.
+    const BUF_LEN: usize = 65536;
+
+    /// A notification received from the OS.
+    pub enum Notification<'a> {
+        /// A batch of rtnetlink messages describing the changes.
+        Messages(&'a [u8]),
+        /// Something has changed, but the OS doesn't say what, or the
+        /// messages describing it were lost.
+        Unknown,
+    }
+
     /// (Not available on iOS/macOS) A utility to monitor for interface changes
     /// and report them, so you can handle events such as WiFi
     /// disconnection/flight mode/route changes
     pub struct IfChangeNotifier {
         inner: InternalIfChangeNotifier,
-        last_ifs: HashSet<Interface>,
+        tracker: IfChangeTracker,
+        buf: Vec<u8>,
     }
 
-    impl IfChangeNotifier {
-        /// Create a new interface change notifier. Returns an OS specific error
-        /// if the network notifier could not be set up.
-        pub fn new() -> io::Result<Self> {
-            Ok(Self {
-                inner: InternalIfChangeNotifier::new()?,
-                last_ifs: HashSet::from_iter(super::get_if_addrs()?),
-            })
+    /// (Not available on iOS/macOS) A builder for interface change notifiers,
+    /// choosing which notifications wake them.
+    ///
+    /// On Linux, notifiers are always woken by link and IPv4/IPv6 address
+    /// notifications, which cover the interface changes they report. Route
+    /// notifications can be added to also report changes to the default
+    /// routes, and neighbor and IPv6 prefix notifications to be woken by
+    /// those too. Other platforms ignore these settings.
+    ///
+    /// ```no_run
+    /// let mut notifier = if_addrs::IfChangeNotifier::builder()
+    ///     .routes(true)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+    pub struct IfChangeNotifierBuilder {
+        routes: bool,
+        neighbors: bool,
+        ipv6_prefixes: bool,
+    }
+
+    impl IfChangeNotifierBuilder {
+        /// Create a builder with the default notifications.
+        pub fn new() -> Self {
+            Self::default()
         }
 
-        /// (Not available on iOS/macOS) Block until the OS reports that the
-        /// network interface list has changed, or until an optional timeout.
+        /// Also wake on IPv4 and IPv6 route changes, and report the changes
+        /// to the default routes.
         ///
-        /// For example, if an ethernet connector is plugged/unplugged, or a
-        /// WiFi network is connected to.
+        /// The default routes are read over rtnetlink. Where its dumps are
+        /// restricted and the interfaces are read with `getifaddrs` instead,
+        /// no default routes are known and no changes to them are reported.
+        pub fn routes(mut self, enable: bool) -> Self {
+            self.routes = enable;
+            self
+        }
+
+        /// Also wake on ARP and NDP neighbor cache changes.
+        pub fn neighbors(mut self, enable: bool) -> Self {
+            self.neighbors = enable;
+            self
+        }
+
+        /// Also wake on IPv6 prefixes learned from router advertisements.
+        pub fn ipv6_prefixes(mut self, enable: bool) -> Self {
+            self.ipv6_prefixes = enable;
+            self
+        }
+
+        /// Create an [`IfChangeNotifier`]. Returns an OS specific error if
+        /// the network notifier could not be set up.
+        pub fn build(&self) -> io::Result<IfChangeNotifier> {
+            #[cfg(windows)]
+            let inner = InternalIfChangeNotifier::new()?;
+            #[cfg(not(windows))]
+            let inner = InternalIfChangeNotifier::new(self.groups())?;
+            Ok(IfChangeNotifier {
+                inner,
+                tracker: IfChangeTracker::new(self.routes)?,
+                buf: vec![0; BUF_LEN],
+            })
+        }
+
+        /// (Unix only, requires the `tokio` feature) Create an
+        /// [`AsyncIfChangeNotifier`], registered with the tokio reactor of the
+        /// current runtime.
         ///
-        /// The changed interfaces are returned. If an interface has both IPv4
-        /// and IPv6 addresses, you can expect both of them to be returned from
-        /// a single call to `wait`.
+        /// # Panics
         ///
-        /// Returns an [`io::ErrorKind::WouldBlock`] error on timeout, or
-        /// another error if the network notifier could not be read from.
-        pub fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Vec<IfChangeType>> {
-            let start = Instant::now();
-            loop {
-                self.inner
-                    .wait(timeout.map(|t| t.saturating_sub(start.elapsed())))?;
+        /// Panics if called outside of a tokio runtime with IO enabled.
+        #[cfg(all(feature = "tokio", unix))]
+        pub fn build_async(&self) -> io::Result<AsyncIfChangeNotifier> {
+            let inner = InternalIfChangeNotifier::new(self.groups())?;
+            Ok(AsyncIfChangeNotifier {
+                inner: tokio::io::unix::AsyncFd::new(inner)?,
+                tracker: IfChangeTracker::new(self.routes)?,
+                buf: vec![0; BUF_LEN],
+            })
+        }
 
-                // something has changed - now we find out what (or whether it was spurious)
-                let new_ifs = HashSet::from_iter(super::get_if_addrs()?);
-                let mut changes: Vec<IfChangeType> = new_ifs
-                    .difference(&self.last_ifs)
-                    .cloned()
-                    .map(IfChangeType::Added)
-                    .collect();
-                changes.extend(
-                    self.last_ifs
-                        .difference(&new_ifs)
-                        .cloned()
-                        .map(IfChangeType::Removed),
-                );
-                self.last_ifs = new_ifs;
+        /// (Unix only, requires the `async-io` feature) Create an
+        /// [`IfChangeStream`].
+        #[cfg(all(feature = "async-io", unix))]
+        pub fn build_stream(&self) -> io::Result<IfChangeStream> {
+            let inner = InternalIfChangeNotifier::new(self.groups())?;
+            Ok(IfChangeStream {
+                inner: async_io::Async::new(inner)?,
+                tracker: IfChangeTracker::new(self.routes)?,
+                buf: vec![0; BUF_LEN],
+            })
+        }
 
-                if !changes.is_empty() {
-                    return Ok(changes);
-                }
+        /// The netlink multicast groups to subscribe to.
+        #[cfg(not(windows))]
+        fn groups(&self) -> u32 {
+            use crate::posix_not_mac::{
+                RTMGRP_IPV4_IFADDR, RTMGRP_IPV4_ROUTE, RTMGRP_IPV6_IFADDR, RTMGRP_IPV6_PREFIX,
+                RTMGRP_IPV6_ROUTE, RTMGRP_LINK, RTMGRP_NEIGH,
+            };
+
+            let mut groups = RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR;
+            if self.routes {
+                groups |= RTMGRP_IPV4_ROUTE | RTMGRP_IPV6_ROUTE;
+            }
+            if self.neighbors {
+                groups |= RTMGRP_NEIGH;
+            }
+            if self.ipv6_prefixes {
+                groups |= RTMGRP_IPV6_PREFIX;
             }
+            groups
         }
     }
-}
 
-#[cfg(not(any(target_os = "macos", target_os = "ios")))]
-pub use if_change_notifier::{IfChangeNotifier, IfChangeType};
+    /// Apply all the notifications that are already queued, without blocking.
+    fn drain(
+        inner: &InternalIfChangeNotifier,
+        tracker: &mut IfChangeTracker,
+        buf: &mut [u8],
+        mut changes: Vec<IfChangeType>,
+    ) -> io::Result<Vec<IfChangeType>> {
+        while let Some(notification) = inner.try_recv(buf)? {
+            tracker.update(notification, &mut changes)?;
+        }
+        Ok(pair_modified(changes))
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{get_if_addrs, Interface};
-    use std::io::Read;
-    use std::net::{IpAddr, Ipv4Addr};
-    use std::process::{Command, Stdio};
-    use std::str::FromStr;
-    use std::thread;
-    use std::time::Duration;
+    /// The last known interfaces, compared with the current ones when the OS
+    /// reports a change.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub(crate) struct IfChangeTracker {
+        last_links: HashMap<u32, NetworkInterface>,
+        last_ifs: HashSet<Interface>,
+    }
 
-    fn list_system_interfaces(cmd: &str, arg: &str) -> String {
-        let start_cmd = if arg == "" {
-            Command::new(cmd).stdout(Stdio::piped()).spawn()
-        } else {
-            Command::new(cmd).arg(arg).stdout(Stdio::piped()).spawn()
-        };
-        let mut process = match start_cmd {
-            Err(why) => {
-                println!("couldn't start cmd {} : {}", cmd, why);
-                return "".to_string();
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    impl IfChangeTracker {
+        pub(crate) fn new(_routes: bool) -> io::Result<Self> {
+            Ok(Self {
+                last_links: current_links()?,
+                last_ifs: current_ifs()?,
+            })
+        }
+
+        /// Find out what has changed since the last notification, if
+        /// anything.
+        pub(crate) fn update(
+            &mut self,
+            _: Notification<'_>,
+            changes: &mut Vec<IfChangeType>,
+        ) -> io::Result<()> {
+            let new_links = current_links()?;
+            for (index, new) in &new_links {
+                match self.last_links.get(index) {
+                    Some(old) => link_changes(old, new, changes),
+                    None => changes.push(IfChangeType::LinkAdded(new.clone())),
+                }
             }
-            Ok(process) => process,
-        };
-        thread::sleep(Duration::from_millis(1000));
-        let _ = process.kill();
-        let result: Vec<u8> = process
-            .stdout
-            .unwrap()
-            .bytes()
-            .map(|x| x.unwrap())
-            .collect();
-        String::from_utf8(result).unwrap()
+            changes.extend(
+                self.last_links
+                    .iter()
+                    .filter(|(index, _)| !new_links.contains_key(index))
+                    .map(|(_, old)| IfChangeType::LinkRemoved(old.clone())),
+            );
+            self.last_links = new_links;
+
+            let new_ifs = current_ifs()?;
+            changes.extend(
+                self.last_ifs
+                    .difference(&new_ifs)
+                    .cloned()
+                    .map(IfChangeType::Removed),
+            );
+            changes.extend(
+                new_ifs
+                    .difference(&self.last_ifs)
+                    .cloned()
+                    .map(IfChangeType::Added),
+            );
+            self.last_ifs = new_ifs;
+            Ok(())
+        }
     }
 
-    #[cfg(windows)]
-    fn list_system_addrs() -> Vec<IpAddr> {
-        use std::net::Ipv6Addr;
-        list_system_interfaces("ipconfig", "")
-            .lines()
-            .filter_map(|line| {
-                println!("{}", line);
-                if line.contains("Address") && !line.contains("Link-local") {
-                    let addr_s: Vec<&str> = line.split(" : ").collect();
-                    if line.contains("IPv6") {
-                        return Some(IpAddr::V6(Ipv6Addr::from_str(addr_s[1]).unwrap()));
-                    } else if line.contains("IPv4") {
-                        return Some(IpAddr::V4(Ipv4Addr::from_str(addr_s[1]).unwrap()));
-                    }
-                }
-                None
-            })
-            .collect()
+    /// Get the current links, keyed by their index.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn current_links() -> io::Result<HashMap<u32, NetworkInterface>> {
+        Ok(super::get_all_links()?
+            .into_iter()
+            .map(|link| (link.index, link))
+            .collect())
     }
 
-    #[cfg(any(target_os = "linux", target_os = "android", target_os = "nacl"))]
-    fn list_system_addrs() -> Vec<IpAddr> {
-        list_system_interfaces("ip", "addr")
-            .lines()
-            .filter_map(|line| {
-                println!("{}", line);
-                if line.contains("inet ") {
-                    let addr_s: Vec<&str> = line.split_whitespace().collect();
-                    let addr: Vec<&str> = addr_s[1].split('/').collect();
-                    return Some(IpAddr::V4(Ipv4Addr::from_str(addr[0]).unwrap()));
-                }
-                None
-            })
-            .collect()
+    /// Get the current interfaces. Their addresses compare equal without
+    /// their lifetimes, so that lifetimes counting down aren't reported as
+    /// changes.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn current_ifs() -> io::Result<HashSet<Interface>> {
+        Ok(HashSet::from_iter(super::get_if_addrs()?))
     }
 
-    #[cfg(any(
-        target_os = "freebsd",
-        target_os = "macos",
-        target_os = "ios",
-        target_os = "tvos"
-    ))]
-    fn list_system_addrs() -> Vec<IpAddr> {
-        list_system_interfaces("ifconfig", "")
-            .lines()
-            .filter_map(|line| {
-                println!("{}", line);
-                if line.contains("inet ") {
-                    let addr_s: Vec<&str> = line.split_whitespace().collect();
-                    return Some(IpAddr::V4(Ipv4Addr::from_str(addr_s[1]).unwrap()));
-                }
-                None
-            })
-            .collect()
+    /// The address of an interface, along with its prefix length and peer,
+    /// which identify it among the addresses of its link.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    type AddrKey = (std::net::IpAddr, u8, Option<std::net::IpAddr>);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn addr_key(interface: &Interface) -> AddrKey {
+        use super::{IfAddr, IfDestination};
+        use std::net::IpAddr;
+
+        match &interface.addr {
+            IfAddr::V4(addr) => (
+                IpAddr::V4(addr.ip),
+                addr.prefixlen,
+                match addr.destination {
+                    IfDestination::Peer(peer) => Some(IpAddr::V4(peer)),
+                    _ => None,
+                },
+            ),
+            IfAddr::V6(addr) => (
+                IpAddr::V6(addr.ip),
+                addr.prefixlen,
+                match addr.destination {
+                    IfDestination::Peer(peer) => Some(IpAddr::V6(peer)),
+                    _ => None,
+                },
+            ),
+        }
     }
 
-    #[test]
-    fn test_get_if_addrs() {
-        let ifaces = get_if_addrs().unwrap();
-        println!("Local interfaces:");
-        println!("{:#?}", ifaces);
-        // at least one loop back address
-        assert!(
-            1 <= ifaces
+    /// The last known links and interfaces, updated from the rtnetlink
+    /// messages describing each change.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) struct IfChangeTracker {
+        /// Which addresses changes are reported for.
+        options: super::GetIfAddrsOptions,
+        links: HashMap<u32, LinkMessage>,
+        /// The interfaces, by link index and then by address. All addresses
+        /// are kept, to match default routes with.
+        ifs: HashMap<u32, HashMap<AddrKey, Interface>>,
+        /// Whether the links could be read over rtnetlink. If not, the
+        /// interfaces are compared in full on every notification.
+        incremental: bool,
+        /// The default routes, if route changes are reported.
+        routes: Option<Vec<RouteMessage>>,
+        gateways: Vec<DefaultGateway>,
+        /// Reads all the links and addresses when resynchronizing.
+        dump: Dump,
+    }
+
+    /// A function reading all the links and addresses over rtnetlink.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    type Dump = fn() -> io::Result<(Vec<LinkMessage>, Vec<netlink::AddrMessage>)>;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn dump() -> io::Result<(Vec<LinkMessage>, Vec<netlink::AddrMessage>)> {
+        let mut socket = RouteSocket::new()?;
+        Ok((socket.links()?, socket.addrs()?))
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    impl IfChangeTracker {
+        pub(crate) fn new(routes: bool) -> io::Result<Self> {
+            let mut tracker = Self {
+                options: super::GetIfAddrsOptions::legacy(),
+                links: HashMap::new(),
+                ifs: HashMap::new(),
+                incremental: false,
+                routes: routes.then(Vec::new),
+                gateways: Vec::new(),
+                dump,
+            };
+            tracker.resync(&mut Vec::new())?;
+            Ok(tracker)
+        }
+
+        /// Create a tracker that knows of `links`, `addrs` and `routes`, as
+        /// if it had read them from rtnetlink.
+        #[cfg(test)]
+        pub(crate) fn with_state(
+            links: Vec<LinkMessage>,
+            addrs: &[netlink::AddrMessage],
+            routes: Option<Vec<RouteMessage>>,
+        ) -> Self {
+            let links: HashMap<_, _> = links.into_iter().map(|link| (link.index, link)).collect();
+            let mut ifs: HashMap<u32, HashMap<AddrKey, Interface>> = HashMap::new();
+            for msg in addrs {
+                if let Some(interface) = links
+                    .get(&msg.index)
+                    .and_then(|link| interface_from(link, msg))
+                {
+                    ifs.entry(msg.index)
+                        .or_default()
+                        .insert(addr_key(&interface), interface);
+                }
+            }
+            let mut tracker = Self {
+                options: super::GetIfAddrsOptions::legacy(),
+                links,
+                ifs,
+                incremental: true,
+                routes,
+                gateways: Vec::new(),
+                dump,
+            };
+            tracker.update_gateways(&mut Vec::new());
+            tracker
+        }
+
+        /// Read the links and addresses with `dump` instead of rtnetlink
+        /// when resynchronizing.
+        #[cfg(test)]
+        pub(crate) fn with_dump(self, dump: Dump) -> Self {
+            Self { dump, ..self }
+        }
+
+        /// Apply a notification, recording what it changed.
+        pub(crate) fn update(
+            &mut self,
+            notification: Notification<'_>,
+            changes: &mut Vec<IfChangeType>,
+        ) -> io::Result<()> {
+            let result = self.track(notification, changes);
+            changes.retain(|change| match change {
+                IfChangeType::Added(interface) | IfChangeType::Removed(interface) => {
+                    self.options.includes(&interface.addr)
+                }
+                _ => true,
+            });
+            result
+        }
+
+        fn track(
+            &mut self,
+            notification: Notification<'_>,
+            changes: &mut Vec<IfChangeType>,
+        ) -> io::Result<()> {
+            let buf = match notification {
+                Notification::Messages(buf) if self.incremental => buf,
+                _ => return self.resync(changes),
+            };
+            let mut gateways_changed = false;
+            let mut reload_routes = false;
+            for change in netlink::changes(buf) {
+                if let Some(index) = change.link_index() {
+                    if self.routes_through(index) {
+                        gateways_changed = true;
+                        // The kernel flushes the IPv4 routes through a link
+                        // when it goes down or loses an address, without
+                        // notifying their removal
+                        reload_routes |= match &change {
+                            Change::NewLink(link) => link.flags & libc::IFF_UP as u32 == 0,
+                            Change::DelLink(_) | Change::DelAddr(_) => true,
+                            _ => false,
+                        };
+                    }
+                }
+                match change {
+                    Change::NewRoute(msg) => gateways_changed |= self.add_route(msg, false),
+                    Change::ReplaceRoute(msg) => gateways_changed |= self.add_route(msg, true),
+                    Change::DelRoute(msg) => gateways_changed |= self.remove_route(&msg),
+                    change => {
+                        if !self.apply(change, changes) {
+                            return self.resync(changes);
+                        }
+                    }
+                }
+            }
+            if reload_routes {
+                self.reload_routes()?;
+            }
+            if gateways_changed {
+                self.update_gateways(changes);
+            }
+            Ok(())
+        }
+
+        /// Whether a tracked default route goes through a link.
+        fn routes_through(&self, index: u32) -> bool {
+            self.routes.iter().flatten().any(|route| {
+                route.oif == Some(index) || route.multipath.iter().any(|hop| hop.oif == index)
+            })
+        }
+
+        /// Add a default route, or update it if it is already known. A
+        /// replacement takes the place of the route with the same
+        /// destination, table and metric. Returns whether the route is
+        /// tracked.
+        fn add_route(&mut self, msg: RouteMessage, replace: bool) -> bool {
+            let routes = match &mut self.routes {
+                Some(routes) if msg.dst_len == 0 => routes,
+                _ => return false,
+            };
+            let same = |route: &&mut RouteMessage| match replace {
+                true => same_slot(route, &msg),
+                false => same_slot(route, &msg) && same_paths(route, &msg),
+            };
+            match routes.iter_mut().find(same) {
+                Some(route) => *route = msg,
+                None => routes.push(msg),
+            }
+            true
+        }
+
+        /// Remove a default route, returning whether it was known.
+        fn remove_route(&mut self, msg: &RouteMessage) -> bool {
+            let routes = match &mut self.routes {
+                Some(routes) => routes,
+                None => return false,
+            };
+            let len = routes.len();
+            routes.retain(|route| !(same_slot(route, msg) && same_paths(route, msg)));
+            routes.len() != len
+        }
+
+        /// Read the default routes again, if they are tracked.
+        fn reload_routes(&mut self) -> io::Result<()> {
+            if let Some(routes) = &mut self.routes {
+                *routes = RouteSocket::new()?
+                    .routes()?
+                    .into_iter()
+                    .filter(|msg| msg.dst_len == 0)
+                    .collect();
+            }
+            Ok(())
+        }
+
+        /// Match the default routes with the known interfaces again,
+        /// recording the changes to the default gateways.
+        fn update_gateways(&mut self, changes: &mut Vec<IfChangeType>) {
+            let routes = match &self.routes {
+                Some(routes) => routes,
+                None => return,
+            };
+            // In a stable order, with primary addresses first as the kernel
+            // lists them
+            let mut interfaces: Vec<_> = self
+                .ifs
+                .values()
+                .flat_map(HashMap::values)
+                .cloned()
+                .collect();
+            interfaces.sort_by_key(|interface| {
+                (
+                    interface.index,
+                    interface
+                        .addr
+                        .flags()
+                        .contains(super::AddressFlags::SECONDARY),
+                    interface.ip(),
+                )
+            });
+            let gateways = route::gateways_from(routes.iter().cloned(), &self.links, &interfaces);
+            gateway_changes(&self.gateways, &gateways, changes);
+            self.gateways = gateways;
+        }
+
+        /// Apply a single change, returning `false` if it can't be applied
+        /// to the known state.
+        fn apply(&mut self, change: Change, changes: &mut Vec<IfChangeType>) -> bool {
+            match change {
+                Change::NewLink(link) => {
+                    let old = self.links.get(&link.index);
+                    match old {
+                        Some(old) => link_changes(&link_from(old), &link_from(&link), changes),
+                        None => changes.push(IfChangeType::LinkAdded(link_from(&link))),
+                    }
+                    for interface in self.ifs.get_mut(&link.index).into_iter().flatten() {
+                        let mut updated = interface.1.clone();
+                        // IPv4 addresses keep their label, unless it is just
+                        // the name of the link. Labels are updated with
+                        // messages of their own when the link is renamed.
+                        let labelled = matches!(old, Some(old) if old.name != updated.name);
+                        if updated.ip().is_ipv6() || !labelled {
+                            updated.name = link.name.clone();
+                        }
+                        updated.flags = posix::flags_from_raw(link.flags as libc::c_int);
+                        updated.hw_addr = link.hw_addr;
+                        // Changes to the link alone are reported by the link
+                        // events above
+                        let old = std::mem::replace(interface.1, updated.clone());
+                        if !same_address(&old, &updated) {
+                            changes.push(IfChangeType::Removed(old));
+                            changes.push(IfChangeType::Added(updated));
+                        }
+                    }
+                    self.links.insert(link.index, link);
+                }
+                Change::DelLink(link) => {
+                    if let Some(old) = self.links.remove(&link.index) {
+                        changes.push(IfChangeType::LinkRemoved(link_from(&old)));
+                    }
+                    if let Some(ifs) = self.ifs.remove(&link.index) {
+                        changes.extend(ifs.into_values().map(IfChangeType::Removed));
+                    }
+                }
+                Change::NewAddr(msg) => {
+                    let link = match self.links.get(&msg.index) {
+                        Some(link) => link,
+                        None => return false,
+                    };
+                    let interface = match interface_from(link, &msg) {
+                        Some(interface) => interface,
+                        None => return true,
+                    };
+                    let ifs = self.ifs.entry(msg.index).or_default();
+                    match ifs.insert(addr_key(&interface), interface.clone()) {
+                        Some(old) if old == interface => {}
+                        old => {
+                            changes.extend(old.map(IfChangeType::Removed));
+                            changes.push(IfChangeType::Added(interface));
+                        }
+                    }
+                }
+                Change::NewRoute(_) | Change::ReplaceRoute(_) | Change::DelRoute(_) => {}
+                Change::DelAddr(msg) => {
+                    let interface = self
+                        .links
+                        .get(&msg.index)
+                        .and_then(|link| interface_from(link, &msg));
+                    if let (Some(interface), Some(ifs)) = (interface, self.ifs.get_mut(&msg.index))
+                    {
+                        changes
+                            .extend(ifs.remove(&addr_key(&interface)).map(IfChangeType::Removed));
+                    }
+                }
+            }
+            true
+        }
+
+        /// Read all the links and interfaces again, recording the changes
+        /// since the known state.
+        fn resync(&mut self, changes: &mut Vec<IfChangeType>) -> io::Result<()> {
+            // Links are only known, and compared, when read from rtnetlink
+            let had_links = self.incremental;
+            let mut links = HashMap::new();
+            let mut ifs: HashMap<u32, HashMap<AddrKey, Interface>> = HashMap::new();
+            let interfaces = match (self.dump)() {
+                Ok((link_msgs, addr_msgs)) => {
+                    links = link_msgs
+                        .into_iter()
+                        .map(|link| (link.index, link))
+                        .collect();
+                    self.incremental = true;
+                    addr_msgs
+                        .iter()
+                        .filter_map(|msg| interface_from(links.get(&msg.index)?, msg))
+                        .collect()
+                }
+                // Fall back to `getifaddrs` where rtnetlink dumps are restricted
+                Err(e) if netlink::is_unavailable(&e) => {
+                    self.incremental = false;
+                    super::get_if_addrs_with(&super::GetIfAddrsOptions::default())?
+                }
+                Err(e) => return Err(e),
+            };
+            for interface in interfaces {
+                ifs.entry(interface.index.unwrap_or(0))
+                    .or_default()
+                    .insert(addr_key(&interface), interface);
+            }
+
+            if had_links && self.incremental {
+                for (index, new) in &links {
+                    match self.links.get(index) {
+                        Some(old) => link_changes(&link_from(old), &link_from(new), changes),
+                        None => changes.push(IfChangeType::LinkAdded(link_from(new))),
+                    }
+                }
+                changes.extend(
+                    self.links
+                        .iter()
+                        .filter(|(index, _)| !links.contains_key(index))
+                        .map(|(_, old)| IfChangeType::LinkRemoved(link_from(old))),
+                );
+            }
+
+            let unchanged = |a: &Interface, b: Option<&Interface>| match b {
+                Some(b) => same_address(a, b),
+                None => false,
+            };
+            for (index, old) in &self.ifs {
+                let new = ifs.get(index);
+                changes.extend(
+                    old.iter()
+                        .filter(|(key, interface)| {
+                            !unchanged(interface, new.and_then(|new| new.get(key)))
+                        })
+                        .map(|(_, interface)| IfChangeType::Removed(interface.clone())),
+                );
+            }
+            for (index, new) in &ifs {
+                let old = self.ifs.get(index);
+                changes.extend(
+                    new.iter()
+                        .filter(|(key, interface)| {
+                            !unchanged(interface, old.and_then(|old| old.get(key)))
+                        })
+                        .map(|(_, interface)| IfChangeType::Added(interface.clone())),
+                );
+            }
+            self.links = links;
+            self.ifs = ifs;
+            if self.incremental {
+                self.reload_routes()?;
+            } else if let Some(routes) = &mut self.routes {
+                routes.clear();
+            }
+            self.update_gateways(changes);
+            Ok(())
+        }
+    }
+
+    /// Whether two interfaces describe the same address, regardless of the
+    /// state of their link, which is reported by link events instead.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn same_address(a: &Interface, b: &Interface) -> bool {
+        (&a.name, &a.addr, a.index) == (&b.name, &b.addr, b.index)
+    }
+
+    /// Whether two routes have the same destination, table and metric.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn same_slot(a: &RouteMessage, b: &RouteMessage) -> bool {
+        (a.family, a.dst_len, a.destination, a.table, a.priority)
+            == (b.family, b.dst_len, b.destination, b.table, b.priority)
+    }
+
+    /// Whether two routes have the same gateways and output interfaces.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn same_paths(a: &RouteMessage, b: &RouteMessage) -> bool {
+        (a.gateway, a.oif) == (b.gateway, b.oif) && a.multipath == b.multipath
+    }
+
+    /// Record the changes between two lists of default gateways.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn gateway_changes(
+        old: &[DefaultGateway],
+        new: &[DefaultGateway],
+        changes: &mut Vec<IfChangeType>,
+    ) {
+        // Changes to the state of the address a route is sent from don't
+        // count
+        let same = |a: &DefaultGateway, b: &DefaultGateway| {
+            a.route == b.route
+                && a.interface.index == b.interface.index
+                && a.interface.ip() == b.interface.ip()
+        };
+        let mut added: Vec<_> = new
+            .iter()
+            .filter(|gateway| !old.iter().any(|old| same(old, gateway)))
+            .collect();
+        for gateway in old
+            .iter()
+            .filter(|gateway| !new.iter().any(|new| same(gateway, new)))
+        {
+            // A route replaced in place keeps its family, table and metric
+            let replaced = added.iter().position(|new| {
+                new.route.destination.is_ipv4() == gateway.route.destination.is_ipv4()
+                    && new.route.table == gateway.route.table
+                    && new.route.metric == gateway.route.metric
+            });
+            changes.push(match replaced {
+                Some(i) => IfChangeType::DefaultRouteChanged {
+                    old: gateway.clone(),
+                    new: added.remove(i).clone(),
+                },
+                None => IfChangeType::DefaultRouteRemoved(gateway.clone()),
+            });
+        }
+        changes.extend(
+            added
+                .into_iter()
+                .cloned()
+                .map(IfChangeType::DefaultRouteAdded),
+        );
+
+        for ipv4 in [true, false] {
+            let preferred = |gateways: &[DefaultGateway]| {
+                gateways
+                    .iter()
+                    .find(|gateway| {
+                        gateway.route.table == Route::MAIN_TABLE
+                            && gateway.route.destination.is_ipv4() == ipv4
+                    })
+                    .cloned()
+            };
+            let hop = |gateway: &Option<DefaultGateway>| {
+                gateway.as_ref().map(|gateway| {
+                    (
+                        gateway.route.gateway,
+                        gateway.route.interface_index,
+                        gateway.interface.ip(),
+                    )
+                })
+            };
+            let (old, new) = (preferred(old), preferred(new));
+            if hop(&old) != hop(&new) {
+                changes.push(IfChangeType::PreferredDefaultRouteChanged { old, new });
+            }
+        }
+    }
+
+    impl IfChangeNotifier {
+        /// Create a new interface change notifier. Returns an OS specific error
+        /// if the network notifier could not be set up.
+        pub fn new() -> io::Result<Self> {
+            IfChangeNotifierBuilder::new().build()
+        }
+
+        /// Configure which notifications wake a new notifier.
+        pub fn builder() -> IfChangeNotifierBuilder {
+            IfChangeNotifierBuilder::new()
+        }
+
+        /// (Not available on iOS/macOS) Block until the OS reports that the
+        /// network interface list has changed, or until an optional timeout.
+        ///
+        /// For example, if an ethernet connector is plugged/unplugged, or a
+        /// WiFi network is connected to.
+        ///
+        /// The changed interfaces are returned. If an interface has both IPv4
+        /// and IPv6 addresses, you can expect both of them to be returned from
+        /// a single call to `wait`.
+        ///
+        /// Returns an [`io::ErrorKind::WouldBlock`] error on timeout, or
+        /// another error if the network notifier could not be read from.
+        pub fn wait(&mut self, timeout: Option<Duration>) -> io::Result<Vec<IfChangeType>> {
+            let start = Instant::now();
+            loop {
+                let notification = self.inner.wait(
+                    timeout.map(|t| t.saturating_sub(start.elapsed())),
+                    &mut self.buf,
+                )?;
+
+                // something has changed - now we find out what (or whether it was spurious)
+                let mut changes = Vec::new();
+                self.tracker.update(notification, &mut changes)?;
+                // Include the changes queued meanwhile, so that an address
+                // removed and added again is reported as modified
+                let changes = drain(&self.inner, &mut self.tracker, &mut self.buf, changes)?;
+                if !changes.is_empty() {
+                    return Ok(changes);
+                }
+            }
+        }
+
+        /// (Not available on iOS/macOS) Return the changes the OS has reported
+        /// since the last call, without blocking. Returns no changes if there
+        /// are none pending.
+        ///
+        /// This is meant to be called from an event loop when the notifier's
+        /// file descriptor becomes readable. It consumes all the pending
+        /// notifications, so it also works with edge-triggered polling.
+        pub fn try_changes(&mut self) -> io::Result<Vec<IfChangeType>> {
+            drain(&self.inner, &mut self.tracker, &mut self.buf, Vec::new())
+        }
+    }
+
+    /// The file descriptor becomes readable when the OS reports a change, at
+    /// which point [`IfChangeNotifier::try_changes`] should be called.
+    #[cfg(unix)]
+    impl std::os::unix::io::AsFd for IfChangeNotifier {
+        fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+            self.inner.as_fd()
+        }
+    }
+
+    #[cfg(unix)]
+    impl std::os::unix::io::AsRawFd for IfChangeNotifier {
+        fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+            self.inner.as_raw_fd()
+        }
+    }
+
+    /// (Unix only, requires the `mio` feature) The notifier can be registered
+    /// with a [`mio::Poll`] for readable events, after which
+    /// [`IfChangeNotifier::try_changes`] should be called.
+    #[cfg(all(feature = "mio", unix))]
+    impl mio::event::Source for IfChangeNotifier {
+        fn register(
+            &mut self,
+            registry: &mio::Registry,
+            token: mio::Token,
+            interests: mio::Interest,
+        ) -> io::Result<()> {
+            use std::os::unix::io::AsRawFd;
+
+            mio::unix::SourceFd(&self.as_raw_fd()).register(registry, token, interests)
+        }
+
+        fn reregister(
+            &mut self,
+            registry: &mio::Registry,
+            token: mio::Token,
+            interests: mio::Interest,
+        ) -> io::Result<()> {
+            use std::os::unix::io::AsRawFd;
+
+            mio::unix::SourceFd(&self.as_raw_fd()).reregister(registry, token, interests)
+        }
+
+        fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+            use std::os::unix::io::AsRawFd;
+
+            mio::unix::SourceFd(&self.as_raw_fd()).deregister(registry)
+        }
+    }
+
+    /// (Unix only, not available on iOS/macOS, requires the `tokio` feature)
+    /// An asynchronous version of [`IfChangeNotifier`], driven by the tokio
+    /// reactor instead of blocking a thread.
+    ///
+    /// ```no_run
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut notifier = if_addrs::AsyncIfChangeNotifier::new()?;
+    /// loop {
+    ///     println!("{:#?}", notifier.next().await?);
+    /// }
+    /// # }
+    /// ```
+    #[cfg(all(feature = "tokio", unix))]
+    pub struct AsyncIfChangeNotifier {
+        inner: tokio::io::unix::AsyncFd<InternalIfChangeNotifier>,
+        tracker: IfChangeTracker,
+        buf: Vec<u8>,
+    }
+
+    #[cfg(all(feature = "tokio", unix))]
+    impl AsyncIfChangeNotifier {
+        /// Create a new interface change notifier, registered with the tokio
+        /// reactor of the current runtime. Returns an OS specific error if
+        /// the network notifier could not be set up.
+        ///
+        /// # Panics
+        ///
+        /// Panics if called outside of a tokio runtime with IO enabled.
+        pub fn new() -> io::Result<Self> {
+            IfChangeNotifierBuilder::new().build_async()
+        }
+
+        /// Wait until the OS reports that the network interface list has
+        /// changed, and return the changed interfaces, like
+        /// [`IfChangeNotifier::wait`].
+        ///
+        /// This method is cancellation safe: if the future is dropped before
+        /// it completes, no changes are lost, and they are returned by the
+        /// next call instead. Use e.g. `tokio::time::timeout` to wait with a
+        /// timeout.
+        pub async fn next(&mut self) -> io::Result<Vec<IfChangeType>> {
+            loop {
+                let mut guard = self.inner.readable().await?;
+                // No await below, so cancelling can't lose notifications
+                let changes = drain(
+                    guard.get_inner(),
+                    &mut self.tracker,
+                    &mut self.buf,
+                    Vec::new(),
+                )?;
+                guard.clear_ready();
+                if !changes.is_empty() {
+                    return Ok(changes);
+                }
+            }
+        }
+    }
+
+    /// (Unix only, not available on iOS/macOS, requires the `async-io`
+    /// feature) A [`Stream`](futures_core::Stream) of interface changes, as
+    /// returned by [`IfChangeNotifier::wait`], that runs on any executor.
+    ///
+    /// The stream never ends, but yields an error if the network notifier
+    /// could not be read from.
+    ///
+    /// ```no_run
+    /// use futures_lite::StreamExt;
+    ///
+    /// let mut changes = if_addrs::IfChangeStream::new().unwrap();
+    /// async_io::block_on(async {
+    ///     while let Some(details) = changes.next().await {
+    ///         println!("{:#?}", details);
+    ///     }
+    /// });
+    /// ```
+    #[cfg(all(feature = "async-io", unix))]
+    pub struct IfChangeStream {
+        inner: async_io::Async<InternalIfChangeNotifier>,
+        tracker: IfChangeTracker,
+        buf: Vec<u8>,
+    }
+
+    #[cfg(all(feature = "async-io", unix))]
+    impl IfChangeStream {
+        /// Create a new stream of interface changes. Returns an OS specific
+        /// error if the network notifier could not be set up.
+        pub fn new() -> io::Result<Self> {
+            IfChangeNotifierBuilder::new().build_stream()
+        }
+    }
+
+    #[cfg(all(feature = "async-io", unix))]
+    impl futures_core::Stream for IfChangeStream {
+        type Item = io::Result<Vec<IfChangeType>>;
+
+        fn poll_next(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Self::Item>> {
+            use std::task::Poll;
+
+            let this = self.get_mut();
+            loop {
+                if let Err(e) = futures_core::ready!(this.inner.poll_readable(cx)) {
+                    return Poll::Ready(Some(Err(e)));
+                }
+                match drain(
+                    this.inner.get_ref(),
+                    &mut this.tracker,
+                    &mut this.buf,
+                    Vec::new(),
+                ) {
+                    Ok(changes) if changes.is_empty() => {}
+                    result => return Poll::Ready(Some(result)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "tokio",
+    unix,
+    not(any(target_os = "macos", target_os = "ios"))
+))]
+pub use if_change_notifier::AsyncIfChangeNotifier;
+#[cfg(all(
+    feature = "async-io",
+    unix,
+    not(any(target_os = "macos", target_os = "ios"))
+))]
+pub use if_change_notifier::IfChangeStream;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+pub use if_change_notifier::{
+    ChangedFields, IfChangeNotifier, IfChangeNotifierBuilder, IfChangeType,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_all_interface_stats, get_if_addrs, get_if_addrs_with, get_interface_by_index,
+        get_interface_by_name, get_interface_stats, get_interfaces, AddressFlags, Clock,
+        CounterWidth, GetIfAddrsOptions, HardwareAddr, IfAddr, IfDestination, Ifv4Addr, Ifv6Addr,
+        Interface, InterfaceStats, Lifetime, LinkLocalPolicy, NetworkInterface, OperState, Scope,
+        StatsSampler,
+    };
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::process::{Command, Stdio};
+    use std::rc::Rc;
+    use std::str::FromStr;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn list_system_interfaces(cmd: &str, arg: &str) -> String {
+        let start_cmd = if arg == "" {
+            Command::new(cmd).stdout(Stdio::piped()).spawn()
+        } else {
+            Command::new(cmd).arg(arg).stdout(Stdio::piped()).spawn()
+        };
+        let mut process = match start_cmd {
+            Err(why) => {
+                println!("couldn't start cmd {} : {}", cmd, why);
+                return "".to_string();
+            }
+            Ok(process) => process,
+        };
+        thread::sleep(Duration::from_millis(1000));
+        let _ = process.kill();
+        let result: Vec<u8> = process
+            .stdout
+            .unwrap()
+            .bytes()
+            .map(|x| x.unwrap())
+            .collect();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[cfg(windows)]
+    fn list_system_addrs() -> Vec<IpAddr> {
+        use std::net::Ipv6Addr;
+        list_system_interfaces("ipconfig", "")
+            .lines()
+            .filter_map(|line| {
+                println!("{}", line);
+                if line.contains("Address") && !line.contains("Link-local") {
+                    let addr_s: Vec<&str> = line.split(" : ").collect();
+                    if line.contains("IPv6") {
+                        return Some(IpAddr::V6(Ipv6Addr::from_str(addr_s[1]).unwrap()));
+                    } else if line.contains("IPv4") {
+                        return Some(IpAddr::V4(Ipv4Addr::from_str(addr_s[1]).unwrap()));
+                    }
+                }
+                None
+            })
+            .collect()
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "nacl"))]
+    fn list_system_addrs() -> Vec<IpAddr> {
+        list_system_interfaces("ip", "addr")
+            .lines()
+            .filter_map(|line| {
+                println!("{}", line);
+                if line.contains("inet ") {
+                    let addr_s: Vec<&str> = line.split_whitespace().collect();
+                    let addr: Vec<&str> = addr_s[1].split('/').collect();
+                    return Some(IpAddr::V4(Ipv4Addr::from_str(addr[0]).unwrap()));
+                }
+                None
+            })
+            .collect()
+    }
+
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos"
+    ))]
+    fn list_system_addrs() -> Vec<IpAddr> {
+        list_system_interfaces("ifconfig", "")
+            .lines()
+            .filter_map(|line| {
+                println!("{}", line);
+                if line.contains("inet ") {
+                    let addr_s: Vec<&str> = line.split_whitespace().collect();
+                    return Some(IpAddr::V4(Ipv4Addr::from_str(addr_s[1]).unwrap()));
+                }
+                None
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_if_addrs() {
+        let ifaces = get_if_addrs().unwrap();
+        println!("Local interfaces:");
+        println!("{:#?}", ifaces);
+        // at least one loop back address
+        assert!(
+            1 <= ifaces
+                .iter()
+                .filter(|interface| interface.is_loopback())
+                .count()
+        );
+        // if index is set, it is non-zero
+        for interface in &ifaces {
+            if let Some(idx) = interface.index {
+                assert!(idx > 0);
+            }
+        }
+
+        // one address of IpV4(127.0.0.1)
+        let is_loopback =
+            |interface: &&Interface| interface.addr.ip() == IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        assert_eq!(1, ifaces.iter().filter(is_loopback).count());
+        // and it is reported by the OS as an up, loopback interface
+        for interface in ifaces.iter().filter(is_loopback) {
+            assert!(interface.is_loopback());
+            assert!(interface.is_up());
+        }
+
+        // each system address shall be listed
+        let system_addrs = list_system_addrs();
+        assert!(!system_addrs.is_empty());
+        for addr in system_addrs {
+            let mut listed = false;
+            println!("\n checking whether {:?} has been properly listed \n", addr);
+            for interface in &ifaces {
+                if interface.addr.ip() == addr {
+                    listed = true;
+                }
+
+                assert!(interface.index.is_some());
+            }
+            assert!(listed);
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_netlink_matches_getifaddrs() {
+        let sorted = |mut ifaces: Vec<Interface>| {
+            ifaces.sort_by_key(|interface| (interface.index, interface.ip()));
+            ifaces
+        };
+        // `getifaddrs` doesn't report address flags or lifetimes
+        let without_flags = |mut interface: Interface| {
+            match interface.addr {
+                IfAddr::V4(ref mut addr) => addr.flags = AddressFlags::empty(),
+                IfAddr::V6(ref mut addr) => addr.flags = AddressFlags::empty(),
+            }
+            interface
+        };
+        let netlink = crate::getifaddrs_netlink::get_if_addrs().unwrap();
+        let netlink = sorted(netlink.into_iter().map(without_flags).collect());
+        let getifaddrs = sorted(crate::getifaddrs_posix::get_if_addrs().unwrap());
+        assert_eq!(netlink, getifaddrs);
+    }
+
+    #[test]
+    fn test_get_if_addrs_preferred_only() {
+        let options = GetIfAddrsOptions {
+            preferred_only: true,
+            ..Default::default()
+        };
+        let ifaces = get_if_addrs_with(&options).unwrap();
+        assert!(ifaces.iter().all(|interface| interface.addr.is_preferred()));
+        assert!(ifaces.iter().any(|interface| interface.is_loopback()));
+    }
+
+    #[test]
+    fn test_get_if_addrs_link_local() {
+        // The default is the same on every platform, whatever the features
+        assert_eq!(
+            GetIfAddrsOptions::default(),
+            GetIfAddrsOptions {
+                preferred_only: false,
+                link_local: LinkLocalPolicy::Include,
+            }
+        );
+
+        let with_policy = |link_local| {
+            let options = GetIfAddrsOptions {
+                link_local,
+                ..Default::default()
+            };
+            get_if_addrs_with(&options).unwrap()
+        };
+        let all = with_policy(LinkLocalPolicy::Include);
+
+        let excluded = with_policy(LinkLocalPolicy::Exclude);
+        assert!(excluded.iter().all(|interface| !interface.is_link_local()));
+        assert_eq!(
+            excluded.len(),
+            all.iter()
+                .filter(|interface| !interface.is_link_local())
+                .count()
+        );
+
+        let is_v6_link_local = |interface: &Interface| matches!(interface.addr, IfAddr::V6(ref addr) if addr.is_link_local());
+        let excluded_v6 = with_policy(LinkLocalPolicy::ExcludeIpv6);
+        assert!(excluded_v6
+            .iter()
+            .all(|interface| !is_v6_link_local(interface)));
+        assert_eq!(
+            excluded_v6.len(),
+            all.iter()
+                .filter(|interface| !is_v6_link_local(interface))
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_get_interfaces() {
+        let links = get_interfaces().unwrap();
+        assert!(links.windows(2).all(|pair| pair[0].index < pair[1].index));
+
+        // Each query sees the addresses at a different time, so they are
+        // matched by index and IP rather than compared whole
+        let key = |link: &NetworkInterface| {
+            let mut ips = link.ips().collect::<Vec<_>>();
+            ips.sort();
+            (link.index, link.name.clone(), ips)
+        };
+
+        // each address is listed under its interface
+        for interface in get_if_addrs().unwrap() {
+            let owner = links
+                .iter()
+                .find(|link| Some(link.index) == interface.index)
+                .unwrap();
+            assert!(
+                owner.ips().any(|ip| ip == interface.ip()),
+                "{:?}",
+                interface
+            );
+        }
+
+        let loopback = links
+            .iter()
+            .find(|link| link.ips().any(|ip| ip == Ipv4Addr::LOCALHOST))
+            .unwrap();
+        assert!(loopback.is_loopback());
+        assert_eq!(
+            get_interface_by_name(&loopback.name)
+                .unwrap()
+                .as_ref()
+                .map(key),
+            Some(key(loopback))
+        );
+        assert_eq!(
+            get_interface_by_index(loopback.index)
+                .unwrap()
+                .as_ref()
+                .map(key),
+            Some(key(loopback))
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_netlink_links_match_getifaddrs() {
+        // `getifaddrs` doesn't report operational states, masters or transmit
+        // queue lengths, nor the link type of links without a hardware address
+        let netlink = crate::getifaddrs_netlink::get_links()
+            .unwrap()
+            .into_iter()
+            .map(|link| NetworkInterface {
+                tx_queue_len: None,
+                link_type: link.hw_addr.and(link.link_type),
+                oper_state: OperState::Unknown,
+                master: None,
+                ..link
+            })
+            .collect::<Vec<_>>();
+        let getifaddrs = crate::getifaddrs_posix::get_links().unwrap();
+        assert_eq!(netlink, getifaddrs);
+    }
+
+    #[cfg(any(
+        windows,
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))]
+    #[test]
+    fn test_get_interface_stats() {
+        let stats = get_all_interface_stats().unwrap();
+        for link in get_interfaces().unwrap() {
+            assert!(stats.contains_key(&link.name), "{:?}", link);
+        }
+
+        let loopback = get_interfaces()
+            .unwrap()
+            .into_iter()
+            .find(|link| link.is_loopback())
+            .unwrap();
+        assert!(get_interface_stats(&loopback.name).unwrap().is_some());
+        assert_eq!(get_interface_stats("no-such-interface").unwrap(), None);
+    }
+
+    struct TestClock(Rc<Cell<Instant>>);
+
+    impl Clock for TestClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn test_stats_sampler() {
+        let time = Rc::new(Cell::new(Instant::now()));
+        let mut sampler = StatsSampler::with_clock(TestClock(time.clone()));
+        let advance = |secs| time.set(time.get() + Duration::from_secs(secs));
+        let sample = |entries: &[(&str, u64, u64)]| {
+            entries
                 .iter()
-                .filter(|interface| interface.is_loopback())
-                .count()
+                .map(|&(name, rx_bytes, tx_packets)| {
+                    let stats = InterfaceStats {
+                        counter_width: CounterWidth::Bits32,
+                        rx_bytes,
+                        tx_packets,
+                        ..Default::default()
+                    };
+                    (name.to_string(), stats)
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        // no rates from the first sample
+        assert!(sampler
+            .update(sample(&[("eth0", 1000, 10), ("wlan0", 0, 0)]))
+            .is_empty());
+
+        advance(2);
+        let rates = sampler.update(sample(&[("eth0", 5000, 30), ("wlan0", 100, 0)]));
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates["eth0"].interval, Duration::from_secs(2));
+        assert_eq!(rates["eth0"].rx_bytes, 2000.0);
+        assert_eq!(rates["eth0"].tx_packets, 10.0);
+        assert_eq!(rates["wlan0"].rx_bytes, 50.0);
+
+        // no time has passed, so the sample is discarded
+        assert!(sampler.update(sample(&[("eth0", 0, 0)])).is_empty());
+
+        // 32-bit wraparound on eth0, and wlan0 disappears
+        advance(1);
+        let rates = sampler.update(sample(&[("eth0", 904, 30)]));
+        assert_eq!(rates.len(), 1);
+        assert_eq!(
+            rates["eth0"].rx_bytes,
+            (u64::from(u32::MAX) - 5000 + 905) as f64
         );
-        // if index is set, it is non-zero
-        for interface in &ifaces {
-            if let Some(idx) = interface.index {
-                assert!(idx > 0);
+
+        // wlan0 comes back with reset counters, and only gets a rate once it
+        // has been seen twice
+        advance(1);
+        let rates = sampler.update(sample(&[("eth0", 904, 30), ("wlan0", 10, 0)]));
+        assert!(!rates.contains_key("wlan0"));
+        advance(1);
+        let rates = sampler.update(sample(&[("eth0", 904, 30), ("wlan0", 20, 0)]));
+        assert_eq!(rates["wlan0"].rx_bytes, 10.0);
+    }
+
+    #[test]
+    fn test_stats_sampler_reset() {
+        let time = Rc::new(Cell::new(Instant::now()));
+        let mut sampler = StatsSampler::with_clock(TestClock(time.clone()));
+        let advance = |secs| time.set(time.get() + Duration::from_secs(secs));
+        let sample = |index, rx_bytes| {
+            let stats = InterfaceStats {
+                index: Some(index),
+                counter_width: CounterWidth::Bits64,
+                rx_bytes,
+                ..Default::default()
+            };
+            HashMap::from_iter([("eth0".to_string(), stats)])
+        };
+
+        sampler.update(sample(2, 1000));
+        advance(1);
+        assert_eq!(sampler.update(sample(2, 3000))["eth0"].rx_bytes, 2000.0);
+
+        // a 64-bit counter that decreases has been reset, even when its
+        // previous value fit in 32 bits, and its increase is unknown
+        advance(1);
+        assert_eq!(sampler.update(sample(2, 100))["eth0"].rx_bytes, 0.0);
+        advance(1);
+        assert_eq!(sampler.update(sample(2, 600))["eth0"].rx_bytes, 500.0);
+
+        // an interface replaced by another of the same name starts afresh
+        advance(1);
+        assert!(sampler.update(sample(3, 5000)).is_empty());
+        advance(1);
+        assert_eq!(sampler.update(sample(3, 5100))["eth0"].rx_bytes, 100.0);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_netlink_stats_match_getifaddrs() {
+        // The counters themselves may change between the two calls
+        let mut netlink = get_all_interface_stats()
+            .unwrap()
+            .into_keys()
+            .collect::<Vec<_>>();
+        let mut getifaddrs = crate::stats::getifaddrs_stats()
+            .unwrap()
+            .into_keys()
+            .collect::<Vec<_>>();
+        netlink.sort();
+        getifaddrs.sort();
+        assert_eq!(netlink, getifaddrs);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_gateways_from() {
+        use crate::netlink::{NextHopMessage, RouteMessage, AF_INET, AF_INET6};
+        use crate::route::gateways_from;
+        use crate::InterfaceFlags;
+
+        let ip = |ip: &str| Some(ip.parse::<IpAddr>().unwrap());
+        let interface = |ip: &str| Interface {
+            name: "eth0".to_string(),
+            addr: if_addr(ip),
+            index: Some(4),
+            flags: InterfaceFlags::UP,
+            hw_addr: None,
+        };
+        let default_route = |family, gateway, oif, multipath| RouteMessage {
+            family,
+            dst_len: 0,
+            table: crate::Route::MAIN_TABLE,
+            protocol: 3,
+            scope: 0,
+            ty: 1,
+            destination: None,
+            gateway,
+            oif,
+            priority: None,
+            prefsrc: None,
+            multipath,
+        };
+        let links = HashMap::new();
+
+        // one gateway for each next hop of a multipath route
+        let hop = |gateway, weight| NextHopMessage {
+            oif: 4,
+            weight,
+            gateway,
+        };
+        let multipath = default_route(
+            AF_INET,
+            None,
+            None,
+            vec![hop(ip("192.0.2.1"), 1), hop(ip("192.0.2.3"), 3)],
+        );
+        let eth0 = [interface("192.0.2.2")];
+        let gateways = gateways_from(vec![multipath], &links, &eth0);
+        assert_eq!(gateways.len(), 2);
+        for (gateway, hop) in gateways.iter().zip(["192.0.2.1", "192.0.2.3"]) {
+            assert_eq!(gateway.route.gateway, ip(hop));
+            assert_eq!(gateway.route.interface_index, Some(4));
+            assert!(gateway.route.nexthops.is_empty());
+            assert_eq!(gateway.interface, eth0[0]);
+        }
+
+        // an IPv6 route on an interface with only a link-local address is
+        // matched with it, but a global address is preferred
+        let v6 = default_route(AF_INET6, ip("fe80::1"), Some(4), Vec::new());
+        let interfaces = [interface("fe80::fc:ff:fe00:1"), interface("fd00::2")];
+        let gateways = gateways_from(vec![v6.clone()], &links, &interfaces[..1]);
+        assert_eq!(gateways.len(), 1);
+        assert_eq!(gateways[0].interface, interfaces[0]);
+
+        let gateways = gateways_from(vec![v6], &links, &interfaces);
+        assert_eq!(gateways.len(), 1);
+        assert_eq!(gateways[0].interface, interfaces[1]);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_get_routes() {
+        let routes = crate::get_routes().unwrap();
+        let loopback = routes
+            .iter()
+            .find(|route| route.destination == IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .unwrap();
+        assert_eq!(loopback.table, crate::Route::LOCAL_TABLE);
+        assert_eq!(loopback.route_type, crate::RouteType::Local);
+        assert_eq!(loopback.prefixlen, 32);
+        assert!(loopback.interface_name.is_some());
+
+        for route in &routes {
+            assert_eq!(
+                route.interface_index.is_some(),
+                route.interface_name.is_some()
+            );
+        }
+
+        let gateways = crate::default_gateways().unwrap();
+        assert!(gateways
+            .windows(2)
+            .all(|pair| pair[0].route.metric <= pair[1].route.metric));
+        for gateway in gateways {
+            assert!(gateway.route.is_default());
+            assert_eq!(gateway.interface.index, gateway.route.interface_index);
+            assert_eq!(
+                gateway.interface.ip().is_ipv4(),
+                gateway.route.destination.is_ipv4()
+            );
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_get_rules() {
+        let rules = crate::get_rules().unwrap();
+        // The kernel's default rules look up the local and main tables
+        for table in [crate::Route::LOCAL_TABLE, crate::Route::MAIN_TABLE] {
+            assert!(rules
+                .iter()
+                .any(|rule| rule.action == crate::RuleAction::Lookup(table)));
+        }
+        let interfaces = crate::get_interfaces().unwrap();
+        for rule in &rules {
+            if let Some(index) = rule.input_interface_index {
+                let interface = interfaces.iter().find(|i| i.index == index).unwrap();
+                assert_eq!(rule.input_interface_name.as_ref(), Some(&interface.name));
             }
         }
+    }
 
-        // one address of IpV4(127.0.0.1)
-        let is_loopback =
-            |interface: &&Interface| interface.addr.ip() == IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
-        assert_eq!(1, ifaces.iter().filter(is_loopback).count());
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_get_neighbors() {
+        let neighbors = crate::get_neighbors().unwrap();
+        let interfaces = crate::get_interfaces().unwrap();
+        for neighbor in &neighbors {
+            let interface = interfaces
+                .iter()
+                .find(|interface| interface.index == neighbor.interface_index)
+                .unwrap();
+            assert_eq!(neighbor.interface_name.as_ref(), Some(&interface.name));
+            if neighbor.state == crate::NeighborState::Permanent {
+                assert!(neighbor.hw_addr.is_some());
+            }
+        }
+    }
 
-        // each system address shall be listed
-        let system_addrs = list_system_addrs();
-        assert!(!system_addrs.is_empty());
-        for addr in system_addrs {
-            let mut listed = false;
-            println!("\n checking whether {:?} has been properly listed \n", addr);
-            for interface in &ifaces {
-                if interface.addr.ip() == addr {
-                    listed = true;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_route_to() {
+        let lookup = crate::route_to(IpAddr::V4(Ipv4Addr::LOCALHOST)).unwrap();
+        assert_eq!(lookup.source, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(lookup.gateway, None);
+        assert!(lookup.interface.is_loopback());
+
+        // Every address of this host is reached from itself
+        for interface in crate::get_if_addrs().unwrap() {
+            if interface.is_up() && !interface.ip().is_ipv6() {
+                let lookup = crate::route_to(interface.ip()).unwrap();
+                assert_eq!(lookup.source, interface.ip());
+                assert_eq!(lookup.interface.name, interface.name);
+            }
+        }
+
+        for gateway in crate::default_gateways().unwrap() {
+            let destination = match gateway.route.destination {
+                IpAddr::V4(_) => "198.51.100.1".parse().unwrap(),
+                IpAddr::V6(_) => "2001:db8::1".parse().unwrap(),
+            };
+            let lookup = crate::route_to(destination).unwrap();
+            // A default route may lead straight to an interface, e.g. a tunnel
+            if gateway.route.gateway.is_some() {
+                assert!(lookup.gateway.is_some());
+            }
+            assert_eq!(lookup.interface.ip(), lookup.source);
+        }
+    }
+
+    #[test]
+    fn test_hardware_addr() {
+        let mac = HardwareAddr::from_str("00:1B:21:3a:4f:5c").unwrap();
+        assert_eq!(mac.as_mac(), Some([0x00, 0x1b, 0x21, 0x3a, 0x4f, 0x5c]));
+        assert_eq!(mac.to_string(), "00:1b:21:3a:4f:5c");
+        assert_eq!(HardwareAddr::from_str("00-1b-21-3a-4f-5c"), Ok(mac));
+
+        // 20-byte InfiniBand address
+        let ib = "80:00:02:08:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:de";
+        let ib_addr = HardwareAddr::from_str(ib).unwrap();
+        assert_eq!(ib_addr.as_bytes().len(), 20);
+        assert_eq!(ib_addr.as_mac(), None);
+        assert_eq!(ib_addr.to_string(), ib);
+
+        assert!(HardwareAddr::from_str("").is_err());
+        assert!(HardwareAddr::from_str("00:1b::3a").is_err());
+        assert!(HardwareAddr::from_str("00:1b:213:3a").is_err());
+        assert!(HardwareAddr::from_str("zz:1b").is_err());
+        assert!(HardwareAddr::new(&[0; HardwareAddr::MAX_LEN + 1]).is_none());
+    }
+
+    fn if_addr(ip: &str) -> IfAddr {
+        match ip.parse::<IpAddr>().unwrap() {
+            IpAddr::V4(ip) => IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask: Ipv4Addr::UNSPECIFIED,
+                prefixlen: 0,
+                destination: IfDestination::None,
+                scope: Scope::of_ipv4(&ip),
+                flags: AddressFlags::empty(),
+                preferred_lifetime: None,
+                valid_lifetime: None,
+                created: None,
+                updated: None,
+            }),
+            IpAddr::V6(ip) => IfAddr::V6(Ifv6Addr {
+                ip,
+                netmask: Ipv6Addr::UNSPECIFIED,
+                prefixlen: 0,
+                destination: IfDestination::None,
+                scope: Scope::of_ipv6(&ip),
+                flags: AddressFlags::empty(),
+                preferred_lifetime: None,
+                valid_lifetime: None,
+                created: None,
+                updated: None,
+            }),
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_classify_destination() {
+        use crate::getifaddrs_posix::classify_destination;
+        use crate::InterfaceFlags;
+
+        let ip = Ipv4Addr::new(10, 9, 0, 1);
+        let other = Ipv4Addr::new(10, 9, 0, 2);
+        let p2p = InterfaceFlags::UP | InterfaceFlags::POINTOPOINT;
+        let broadcast = InterfaceFlags::UP | InterfaceFlags::BROADCAST;
+        assert_eq!(
+            classify_destination(p2p, ip, other),
+            IfDestination::Peer(other)
+        );
+        // A point-to-point interface without a peer reports its own address
+        assert_eq!(classify_destination(p2p, ip, ip), IfDestination::None);
+        assert_eq!(
+            classify_destination(broadcast, ip, other),
+            IfDestination::Broadcast(other)
+        );
+        assert_eq!(
+            classify_destination(InterfaceFlags::UP, ip, other),
+            IfDestination::None
+        );
+    }
+
+    #[test]
+    fn test_address_classification() {
+        let scopes = [
+            ("127.0.0.1", Scope::Host),
+            ("169.254.1.1", Scope::Link),
+            ("10.0.0.1", Scope::Global),
+            ("::1", Scope::Host),
+            ("fe80::1", Scope::Link),
+            ("febf::1", Scope::Link),
+            ("fec0::1", Scope::Site),
+            ("fd00::1", Scope::Global),
+        ];
+        for (ip, scope) in scopes {
+            assert_eq!(if_addr(ip).scope(), scope, "{}", ip);
+        }
+
+        for ip in ["10.1.2.3", "172.16.0.1", "172.31.255.255", "192.168.1.1"] {
+            assert!(if_addr(ip).is_private(), "{}", ip);
+            assert!(!if_addr(ip).is_global(), "{}", ip);
+        }
+        assert!(!if_addr("172.32.0.1").is_private());
+        assert!(if_addr("100.64.0.1").is_shared());
+        assert!(!if_addr("100.128.0.1").is_shared());
+        assert!(if_addr("fc00::1").is_unique_local());
+        assert!(if_addr("fdff::1").is_unique_local());
+        assert!(!if_addr("fe00::1").is_unique_local());
+
+        for ip in [
+            "192.0.2.1",
+            "198.51.100.1",
+            "203.0.113.1",
+            "2001:db8::1",
+            "3fff::1",
+        ] {
+            assert!(if_addr(ip).is_documentation(), "{}", ip);
+            assert!(!if_addr(ip).is_global(), "{}", ip);
+        }
+
+        for ip in ["1.1.1.1", "192.0.0.9", "2606:4700::1111", "2001:4:112::1"] {
+            assert!(if_addr(ip).is_global(), "{}", ip);
+        }
+        for ip in [
+            "0.1.2.3",
+            "100.64.0.1",
+            "192.0.0.1",
+            "198.18.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::ffff:1.1.1.1",
+            "2001::1",
+            "fe80::1",
+            "fec0::1",
+        ] {
+            assert!(!if_addr(ip).is_global(), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_addr_eq_ignores_lifetimes() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |addr: &IfAddr| {
+            let mut hasher = DefaultHasher::new();
+            addr.hash(&mut hasher);
+            hasher.finish()
+        };
+        for ip in ["192.0.2.1", "2001:db8::1"] {
+            let old = if_addr(ip);
+            let mut new = old.clone();
+            match new {
+                IfAddr::V4(ref mut addr) => {
+                    addr.preferred_lifetime = Some(Lifetime::Finite(Duration::from_secs(60)));
+                    addr.valid_lifetime = Some(Lifetime::Forever);
+                    addr.updated = Some(Duration::from_secs(5));
+                }
+                IfAddr::V6(ref mut addr) => {
+                    addr.preferred_lifetime = Some(Lifetime::Finite(Duration::from_secs(60)));
+                    addr.valid_lifetime = Some(Lifetime::Forever);
+                    addr.updated = Some(Duration::from_secs(5));
                 }
+            }
+            assert_eq!(old, new);
+            assert_eq!(hash(&old), hash(&new));
 
-                assert!(interface.index.is_some());
+            match new {
+                IfAddr::V4(ref mut addr) => addr.flags = AddressFlags::DEPRECATED,
+                IfAddr::V6(ref mut addr) => addr.flags = AddressFlags::DEPRECATED,
             }
-            assert!(listed);
+            assert_ne!(old, new);
         }
     }
 
@@ -615,4 +3549,662 @@ mod tests {
 
         assert!(notifier.wait(Some(Duration::ZERO)).is_err());
     }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    #[test]
+    fn test_if_notifier_try_changes() {
+        // As above, no changes are expected, and checking must not block
+        let mut notifier = crate::IfChangeNotifier::new().unwrap();
+        assert_eq!(notifier.try_changes().unwrap(), vec![]);
+
+        let mut notifier = crate::IfChangeNotifier::builder()
+            .routes(true)
+            .neighbors(true)
+            .ipv6_prefixes(true)
+            .build()
+            .unwrap();
+        assert!(notifier.wait(Some(Duration::ZERO)).is_err());
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    #[test]
+    fn test_if_change_tracker() {
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+
+        // Rereading all the interfaces must agree with the known state, as
+        // nothing has changed. An empty batch of messages changes nothing
+        // either.
+        let mut tracker = IfChangeTracker::new(true).unwrap();
+        let mut changes = Vec::new();
+        tracker.update(Notification::Unknown, &mut changes).unwrap();
+        assert_eq!(changes, vec![]);
+        tracker
+            .update(Notification::Messages(&[]), &mut changes)
+            .unwrap();
+        assert_eq!(changes, vec![]);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    #[test]
+    fn test_pair_modified() {
+        use crate::if_change_notifier::pair_modified;
+        use crate::{ChangedFields, IfChangeType, InterfaceFlags};
+
+        let interface = |index, ip| Interface {
+            name: "eth0".to_string(),
+            addr: if_addr(ip),
+            index: Some(index),
+            flags: InterfaceFlags::UP,
+            hw_addr: None,
+            #[cfg(windows)]
+            adapter_name: String::new(),
+        };
+        let old = interface(2, "192.0.2.1");
+        let mut new = old.clone();
+        if let IfAddr::V4(ref mut addr) = new.addr {
+            addr.prefixlen = 24;
+            addr.netmask = Ipv4Addr::new(255, 255, 255, 0);
+            addr.destination = IfDestination::Broadcast(Ipv4Addr::new(192, 0, 2, 255));
+        }
+        let other = interface(3, "192.0.2.1");
+
+        // The same IP on another interface is not paired
+        let changes = vec![
+            IfChangeType::Removed(old.clone()),
+            IfChangeType::Added(other.clone()),
+            IfChangeType::Added(new.clone()),
+        ];
+        assert_eq!(
+            pair_modified(changes),
+            vec![
+                IfChangeType::Modified {
+                    old: old.clone(),
+                    new: new.clone(),
+                    changed: ChangedFields::NETMASK | ChangedFields::DESTINATION,
+                },
+                IfChangeType::Added(other.clone()),
+            ]
+        );
+
+        // An address added before it was removed is not paired
+        let changes = vec![
+            IfChangeType::Added(new.clone()),
+            IfChangeType::Removed(old.clone()),
+        ];
+        assert_eq!(pair_modified(changes.clone()), changes);
+
+        // Re-adding an identical address is no change at all
+        let mut readded = old.clone();
+        if let IfAddr::V4(ref mut addr) = readded.addr {
+            addr.created = Some(Duration::from_secs(1));
+        }
+        let changes = vec![IfChangeType::Removed(old), IfChangeType::Added(readded)];
+        assert_eq!(pair_modified(changes), vec![]);
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    #[test]
+    fn test_link_changes() {
+        use crate::if_change_notifier::link_changes;
+        use crate::{IfChangeType, InterfaceFlags, LinkType};
+
+        let link = NetworkInterface {
+            name: "eth0".to_string(),
+            index: 2,
+            flags: InterfaceFlags::UP | InterfaceFlags::RUNNING,
+            hw_addr: Some("02:00:5e:00:53:01".parse().unwrap()),
+            addrs: vec![],
+            mtu: Some(1500),
+            tx_queue_len: Some(1000),
+            link_type: Some(LinkType::Ethernet),
+            oper_state: OperState::Up,
+            master: None,
+            #[cfg(windows)]
+            adapter_name: "{00000000-0000-0000-0000-000000000002}".to_string(),
+        };
+        let changes = |new: &NetworkInterface| {
+            let mut changes = Vec::new();
+            link_changes(&link, new, &mut changes);
+            changes
+        };
+        assert_eq!(changes(&link), vec![]);
+
+        let no_carrier = NetworkInterface {
+            flags: InterfaceFlags::UP,
+            ..link.clone()
+        };
+        assert_eq!(
+            changes(&no_carrier),
+            vec![IfChangeType::CarrierChanged(no_carrier.clone())]
+        );
+
+        // Losing the carrier along with the admin state is only reported as
+        // the latter
+        let down = NetworkInterface {
+            flags: InterfaceFlags::empty(),
+            ..link.clone()
+        };
+        assert_eq!(changes(&down), vec![IfChangeType::LinkDown(down.clone())]);
+        let mut up = Vec::new();
+        link_changes(&down, &link, &mut up);
+        assert_eq!(up, vec![IfChangeType::LinkUp(link.clone())]);
+
+        let renamed = NetworkInterface {
+            name: "renamed0".to_string(),
+            mtu: Some(9000),
+            ..link.clone()
+        };
+        assert_eq!(
+            changes(&renamed),
+            vec![
+                IfChangeType::Renamed {
+                    old: link.clone(),
+                    new: renamed.clone(),
+                },
+                IfChangeType::MtuChanged {
+                    old: link.clone(),
+                    new: renamed.clone(),
+                },
+            ]
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_gateway_changes() {
+        use crate::if_change_notifier::gateway_changes;
+        use crate::{
+            DefaultGateway, IfChangeType, InterfaceFlags, Route, RouteProtocol, RouteType,
+        };
+
+        let changes = |old: &[_], new: &[_]| {
+            let mut changes = Vec::new();
+            gateway_changes(old, new, &mut changes);
+            changes
+        };
+        let preferred = DefaultGateway {
+            route: Route {
+                destination: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                prefixlen: 0,
+                gateway: Some("192.0.2.1".parse().unwrap()),
+                source: Some("192.0.2.2".parse().unwrap()),
+                interface_index: Some(4),
+                interface_name: Some("eth0".to_string()),
+                metric: 100,
+                table: Route::MAIN_TABLE,
+                protocol: RouteProtocol::Boot,
+                scope: Scope::Global,
+                route_type: RouteType::Unicast,
+                nexthops: Vec::new(),
+            },
+            interface: Interface {
+                name: "eth0".to_string(),
+                addr: if_addr("192.0.2.2"),
+                index: Some(4),
+                flags: InterfaceFlags::UP | InterfaceFlags::RUNNING,
+                hw_addr: None,
+            },
+        };
+        let gateways = [preferred.clone()];
+        assert_eq!(changes(&gateways, &gateways), vec![]);
+        assert_eq!(
+            changes(&[], &gateways),
+            vec![
+                IfChangeType::DefaultRouteAdded(preferred.clone()),
+                IfChangeType::PreferredDefaultRouteChanged {
+                    old: None,
+                    new: Some(preferred.clone()),
+                },
+            ]
+        );
+
+        // Replacing the gateway of a route is a single change
+        let mut replaced = preferred.clone();
+        replaced.route.gateway = Some("192.0.2.254".parse().unwrap());
+        assert_eq!(
+            changes(&gateways, &[replaced.clone()]),
+            vec![
+                IfChangeType::DefaultRouteChanged {
+                    old: preferred.clone(),
+                    new: replaced.clone(),
+                },
+                IfChangeType::PreferredDefaultRouteChanged {
+                    old: Some(preferred.clone()),
+                    new: Some(replaced.clone()),
+                },
+            ]
+        );
+
+        // A backup route with a higher metric doesn't change the preferred one
+        let mut backup = replaced.clone();
+        backup.route.metric += 100;
+        assert_eq!(
+            changes(&[replaced.clone()], &[replaced.clone(), backup.clone()]),
+            vec![IfChangeType::DefaultRouteAdded(backup)]
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_notifications() {
+        use crate::getifaddrs_netlink::{interface_from, link_from};
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_link, parse_link, NOTIFICATIONS};
+        use crate::netlink::{self, Change, RTM_NEWLINK};
+        use crate::IfChangeType;
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let mut tracker = IfChangeTracker::with_state(vec![eth0.clone()], &[], None);
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(NOTIFICATIONS), &mut changes)
+            .unwrap();
+
+        // The address is added and removed again, then a link is created and
+        // deleted
+        let added = match netlink::changes(NOTIFICATIONS).next() {
+            Some(Change::NewAddr(msg)) => interface_from(&eth0, &msg).unwrap(),
+            other => panic!("unexpected change {:?}", other),
+        };
+        assert_eq!(added.name, "eth0");
+        assert_eq!(added.ip(), "192.0.2.77".parse::<IpAddr>().unwrap());
+        let vz0 = match netlink::changes(NOTIFICATIONS).nth(2) {
+            Some(Change::NewLink(link)) => link_from(&link),
+            other => panic!("unexpected change {:?}", other),
+        };
+        assert_eq!(vz0.name, "vz0");
+        assert_eq!(
+            changes,
+            vec![
+                IfChangeType::Added(added.clone()),
+                IfChangeType::Removed(added),
+                IfChangeType::LinkAdded(vz0.clone()),
+                IfChangeType::LinkRemoved(vz0),
+            ]
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_rename() {
+        use crate::getifaddrs_netlink::{interface_from, link_from};
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{AddrMessage, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::IfChangeType;
+        use std::collections::HashSet;
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let addrs = [
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0")),
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.9", 24, "eth0:1")),
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "fd00::2", 64, "")),
+        ];
+        let mut tracker = IfChangeTracker::with_state(vec![eth0.clone()], &addrs, None);
+
+        let rename = build_link(RTM_NEWLINK, 4, "wan0");
+        let wan0 = parse_link(&rename);
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&rename), &mut changes)
+            .unwrap();
+        assert_eq!(
+            changes[0],
+            IfChangeType::Renamed {
+                old: link_from(&eth0),
+                new: link_from(&wan0),
+            }
+        );
+
+        // The addresses named after the link follow it, but the one with a
+        // label of its own keeps it, until the kernel updates the label
+        let renamed = |msg: &AddrMessage| {
+            let old = interface_from(&eth0, msg).unwrap();
+            let new = crate::Interface {
+                name: "wan0".to_string(),
+                ..old.clone()
+            };
+            [IfChangeType::Removed(old), IfChangeType::Added(new)]
+        };
+        let expected: HashSet<_> = renamed(&addrs[0])
+            .into_iter()
+            .chain(renamed(&addrs[2]))
+            .collect();
+        assert_eq!(changes.len(), 5);
+        assert_eq!(
+            changes[1..].iter().cloned().collect::<HashSet<_>>(),
+            expected
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_link_down() {
+        use crate::getifaddrs_netlink::link_from;
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{RTM_DELADDR, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::{IfChangeType, InterfaceFlags};
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let addrs = [
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0")),
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "fd00::2", 64, "")),
+        ];
+        let mut tracker = IfChangeTracker::with_state(vec![eth0], &addrs, None);
+
+        // Bringing the link down is reported once, not for each address
+        let mut down = build_link(RTM_NEWLINK, 4, "eth0");
+        down[24..28].copy_from_slice(&(libc::IFF_BROADCAST as u32).to_ne_bytes());
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&down), &mut changes)
+            .unwrap();
+        assert_eq!(
+            changes,
+            vec![IfChangeType::LinkDown(link_from(&parse_link(&down)))]
+        );
+
+        // but the addresses are updated along with it
+        let buf = build_addr(RTM_DELADDR, 4, "192.0.2.2", 24, "eth0");
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        match &changes[..] {
+            [IfChangeType::Removed(interface)] => {
+                assert_eq!(interface.flags, InterfaceFlags::BROADCAST)
+            }
+            other => panic!("unexpected changes {:?}", other),
+        }
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_removed_link() {
+        use crate::getifaddrs_netlink::{interface_from, link_from};
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{RTM_DELADDR, RTM_DELLINK, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::IfChangeType;
+        use std::collections::HashSet;
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let addrs = [
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0")),
+            parse_addr(&build_addr(RTM_NEWADDR, 4, "fd00::2", 64, "")),
+        ];
+        let mut tracker = IfChangeTracker::with_state(vec![eth0.clone()], &addrs, None);
+
+        // Deleting the link removes it and its addresses, and the address
+        // notifications that follow it change nothing more
+        let buf = [
+            build_link(RTM_DELLINK, 4, "eth0"),
+            build_addr(RTM_DELADDR, 4, "192.0.2.2", 24, "eth0"),
+        ]
+        .concat();
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        assert_eq!(changes[0], IfChangeType::LinkRemoved(link_from(&eth0)));
+        let expected: HashSet<_> = addrs
+            .iter()
+            .map(|msg| IfChangeType::Removed(interface_from(&eth0, msg).unwrap()))
+            .collect();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[1..].iter().cloned().collect::<HashSet<_>>(),
+            expected
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_unknown_link_resyncs() {
+        use crate::getifaddrs_netlink::{interface_from, link_from};
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{AddrMessage, LinkMessage, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::IfChangeType;
+        use std::collections::HashSet;
+        use std::io;
+
+        fn eth0() -> (LinkMessage, AddrMessage) {
+            let link = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+            let addr = parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0"));
+            (link, addr)
+        }
+        fn wan1() -> (LinkMessage, AddrMessage) {
+            let link = parse_link(&build_link(RTM_NEWLINK, 7, "wan1"));
+            let addr = parse_addr(&build_addr(RTM_NEWADDR, 7, "198.51.100.5", 24, "wan1"));
+            (link, addr)
+        }
+        // The state read when resynchronizing, in which fake0 is gone and
+        // wan1 has appeared
+        fn dump() -> io::Result<(Vec<LinkMessage>, Vec<AddrMessage>)> {
+            let ((eth0, eth0_addr), (wan1, wan1_addr)) = (eth0(), wan1());
+            Ok((vec![eth0, wan1], vec![eth0_addr, wan1_addr]))
+        }
+
+        let (eth0, eth0_addr) = eth0();
+        let fake = parse_link(&build_link(RTM_NEWLINK, 6, "fake0"));
+        let fake_addr = parse_addr(&build_addr(RTM_NEWADDR, 6, "203.0.113.7", 24, "fake0"));
+        let mut tracker = IfChangeTracker::with_state(
+            vec![eth0, fake.clone()],
+            &[eth0_addr, fake_addr.clone()],
+            None,
+        )
+        .with_dump(dump);
+
+        // An address on a link the tracker doesn't know can't be applied, so
+        // everything is read again
+        let (wan1, wan1_addr) = wan1();
+        let buf = build_addr(RTM_NEWADDR, 7, "198.51.100.5", 24, "wan1");
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        let expected = HashSet::from([
+            IfChangeType::LinkAdded(link_from(&wan1)),
+            IfChangeType::LinkRemoved(link_from(&fake)),
+            IfChangeType::Added(interface_from(&wan1, &wan1_addr).unwrap()),
+            IfChangeType::Removed(interface_from(&fake, &fake_addr).unwrap()),
+        ]);
+        assert_eq!(changes.len(), expected.len());
+        assert_eq!(changes.into_iter().collect::<HashSet<_>>(), expected);
+
+        // The address is now known, so the notification changes nothing
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        assert_eq!(changes, vec![]);
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_bridge_port() {
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{RTM_DELADDR, RTM_DELLINK, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::IfChangeType;
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let addr = parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0"));
+        let mut tracker = IfChangeTracker::with_state(vec![eth0], &[addr], None);
+
+        // When eth0 leaves a bridge, an AF_BRIDGE message deletes the port
+        // while the link and its addresses remain, and a partial AF_BRIDGE
+        // message describes it otherwise
+        let mut buf = Vec::new();
+        for (ty, name) in [(RTM_DELLINK, "eth0"), (RTM_NEWLINK, "")] {
+            let mut msg = build_link(ty, 4, name);
+            msg[16] = libc::AF_BRIDGE as u8;
+            buf.extend(msg);
+        }
+        let mut changes = Vec::new();
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        assert_eq!(changes, vec![]);
+
+        // So the address is still known, and deleting it is reported
+        let buf = build_addr(RTM_DELADDR, 4, "192.0.2.2", 24, "eth0");
+        tracker
+            .update(Notification::Messages(&buf), &mut changes)
+            .unwrap();
+        assert!(matches!(&changes[..], [IfChangeType::Removed(_)]));
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        target_endian = "little"
+    ))]
+    #[test]
+    fn test_tracker_routes() {
+        use crate::getifaddrs_netlink::interface_from;
+        use crate::if_change_notifier::{IfChangeTracker, Notification};
+        use crate::netlink::tests::ROUTE_NOTIFICATIONS;
+        use crate::netlink::tests::{build_addr, build_link, parse_addr, parse_link};
+        use crate::netlink::{self, Change, RTM_NEWADDR, RTM_NEWLINK};
+        use crate::route::gateways_from;
+        use crate::IfChangeType;
+
+        let eth0 = parse_link(&build_link(RTM_NEWLINK, 4, "eth0"));
+        let addr = parse_addr(&build_addr(RTM_NEWADDR, 4, "192.0.2.2", 24, "eth0"));
+        let mut tracker = IfChangeTracker::with_state(
+            vec![eth0.clone()],
+            std::slice::from_ref(&addr),
+            Some(Vec::new()),
+        );
+        let gateway = |buf: &[u8]| {
+            let route = match netlink::changes(buf).next() {
+                Some(Change::NewRoute(route) | Change::ReplaceRoute(route)) => route,
+                other => panic!("unexpected change {:?}", other),
+            };
+            let links = HashMap::from([(4, eth0.clone())]);
+            let interfaces = [interface_from(&eth0, &addr).unwrap()];
+            gateways_from(vec![route], &links, &interfaces).remove(0)
+        };
+
+        // The route is added, then removed. The default routes are compared
+        // after each batch, so neither would be reported if the two
+        // notifications arrived together.
+        let (add, del) = ROUTE_NOTIFICATIONS.split_at(60);
+        let added = gateway(add);
+        let mut changes = Vec::new();
+        for buf in [add, del] {
+            tracker
+                .update(Notification::Messages(buf), &mut changes)
+                .unwrap();
+        }
+        assert_eq!(
+            changes,
+            vec![
+                IfChangeType::DefaultRouteAdded(added.clone()),
+                IfChangeType::PreferredDefaultRouteChanged {
+                    old: None,
+                    new: Some(added.clone()),
+                },
+                IfChangeType::DefaultRouteRemoved(added.clone()),
+                IfChangeType::PreferredDefaultRouteChanged {
+                    old: Some(added.clone()),
+                    new: None,
+                },
+            ]
+        );
+
+        // A replacement with another gateway takes the place of the route,
+        // without a removal being notified
+        let mut replace = add.to_vec();
+        replace[6..8].copy_from_slice(&0x500u16.to_ne_bytes());
+        replace[51] = 254;
+        let replaced = gateway(&replace);
+        assert_eq!(replaced.route.gateway, Some("192.0.2.254".parse().unwrap()));
+        let mut changes = Vec::new();
+        for buf in [add, &replace] {
+            tracker
+                .update(Notification::Messages(buf), &mut changes)
+                .unwrap();
+        }
+        assert_eq!(
+            changes[2..],
+            [
+                IfChangeType::DefaultRouteChanged {
+                    old: added.clone(),
+                    new: replaced.clone(),
+                },
+                IfChangeType::PreferredDefaultRouteChanged {
+                    old: Some(added),
+                    new: Some(replaced),
+                },
+            ]
+        );
+    }
+
+    #[cfg(all(
+        feature = "mio",
+        unix,
+        not(any(target_os = "macos", target_os = "ios"))
+    ))]
+    #[test]
+    fn test_if_notifier_mio() {
+        let mut poll = mio::Poll::new().unwrap();
+        let mut events = mio::Events::with_capacity(8);
+        let mut notifier = crate::IfChangeNotifier::new().unwrap();
+        poll.registry()
+            .register(&mut notifier, mio::Token(0), mio::Interest::READABLE)
+            .unwrap();
+        poll.poll(&mut events, Some(Duration::ZERO)).unwrap();
+        assert!(events.is_empty());
+        poll.registry().deregister(&mut notifier).unwrap();
+    }
+
+    #[cfg(all(
+        feature = "tokio",
+        unix,
+        not(any(target_os = "macos", target_os = "ios"))
+    ))]
+    #[tokio::test]
+    async fn test_async_if_notifier() {
+        // As above, check that the notifier can start up and doesn't report a
+        // change straight away.
+        let mut notifier = crate::AsyncIfChangeNotifier::new().unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(10), notifier.next());
+        assert!(next.await.is_err());
+    }
+
+    #[cfg(all(
+        feature = "async-io",
+        unix,
+        not(any(target_os = "macos", target_os = "ios"))
+    ))]
+    #[test]
+    fn test_if_change_stream() {
+        use futures_lite::{future, StreamExt};
+
+        // As above, check that the stream can start up and doesn't report a
+        // change straight away.
+        let mut changes = crate::IfChangeStream::new().unwrap();
+        assert!(future::block_on(future::poll_once(changes.next())).is_none());
+    }
 }
@@ -0,0 +1,116 @@
+use crate::netlink::{self, LinkMessage, NeighborMessage, RouteSocket};
+use crate::HardwareAddr;
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+
+/// The state of a neighbor cache entry (`NUD_*`).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum NeighborState {
+    /// Address resolution is in progress.
+    Incomplete,
+    /// The neighbor was recently confirmed to be reachable.
+    Reachable,
+    /// The entry has not been confirmed for a while, and will be verified
+    /// when it is next used.
+    Stale,
+    /// The entry was used while stale, and is waiting for confirmation by
+    /// upper-layer traffic before being probed.
+    Delay,
+    /// The neighbor is being probed.
+    Probe,
+    /// Address resolution failed.
+    Failed,
+    /// The interface doesn't need address resolution, such as the loopback
+    /// interface, or the address is a multicast address.
+    NoArp,
+    /// The entry was added by the administrator and never expires.
+    Permanent,
+    /// The entry has no state yet.
+    None,
+    /// Any other state, as the raw value.
+    Other(u16),
+}
+
+impl NeighborState {
+    fn from_raw(state: u16) -> Self {
+        match state {
+            0x00 => NeighborState::None,
+            0x01 => NeighborState::Incomplete,
+            0x02 => NeighborState::Reachable,
+            0x04 => NeighborState::Stale,
+            0x08 => NeighborState::Delay,
+            0x10 => NeighborState::Probe,
+            0x20 => NeighborState::Failed,
+            0x40 => NeighborState::NoArp,
+            0x80 => NeighborState::Permanent,
+            other => NeighborState::Other(other),
+        }
+    }
+
+    /// Check whether the entry holds a usable link-layer address, i.e. it is
+    /// neither being resolved nor failed.
+    pub fn is_valid(&self) -> bool {
+        !matches!(
+            self,
+            NeighborState::Incomplete | NeighborState::Failed | NeighborState::None
+        )
+    }
+}
+
+/// (Linux/Android only) An entry of the ARP (IPv4) or NDP (IPv6) neighbor
+/// cache, as listed by `ip neigh`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Neighbor {
+    /// The IP address of the neighbor.
+    pub ip: IpAddr,
+    /// The link-layer address of the neighbor, if it is known.
+    pub hw_addr: Option<HardwareAddr>,
+    /// The state of the entry.
+    pub state: NeighborState,
+    /// The index of the interface the neighbor is reached through.
+    pub interface_index: u32,
+    /// The name of that interface.
+    pub interface_name: Option<String>,
+}
+
+impl Neighbor {
+    fn from_message(msg: NeighborMessage, links: &HashMap<u32, LinkMessage>) -> Option<Self> {
+        // Skip bridge forwarding entries, which have no IP address
+        if msg.family != netlink::AF_INET && msg.family != netlink::AF_INET6 {
+            return None;
+        }
+        Some(Self {
+            ip: msg.destination?,
+            hw_addr: msg.lladdr,
+            state: NeighborState::from_raw(msg.state),
+            interface_index: msg.index,
+            interface_name: links.get(&msg.index).map(|link| link.name.clone()),
+        })
+    }
+}
+
+/// (Linux/Android only) Get the entries of the IPv4 ARP and IPv6 NDP neighbor
+/// caches of all interfaces.
+///
+/// This includes the entries in the [`NeighborState::NoArp`] state that `ip
+/// neigh` hides, such as those for multicast addresses.
+///
+/// ```no_run
+/// for neighbor in if_addrs::get_neighbors().unwrap() {
+///     println!("{} {:?} {:?}", neighbor.ip, neighbor.hw_addr, neighbor.state);
+/// }
+/// ```
+pub fn get_neighbors() -> io::Result<Vec<Neighbor>> {
+    let mut socket = RouteSocket::new()?;
+    let links: HashMap<u32, _> = socket
+        .links()?
+        .into_iter()
+        .map(|link| (link.index, link))
+        .collect();
+    Ok(socket
+        .neighbors()?
+        .into_iter()
+        .filter_map(|msg| Neighbor::from_message(msg, &links))
+        .collect())
+}
@@ -8,11 +8,188 @@
 // Software.
 
 use crate::sockaddr;
-use libc::{freeifaddrs, getifaddrs, ifaddrs};
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+))]
+use crate::CounterWidth;
+use crate::{InterfaceFlags, InterfaceStats, LinkType};
+use libc::{c_int, freeifaddrs, getifaddrs, ifaddrs};
+use std::ffi::CStr;
 use std::net::IpAddr;
 use std::{io, mem};
 
-pub fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
+pub fn interface_flags(ifaddr: &ifaddrs) -> InterfaceFlags {
+    flags_from_raw(ifaddr.ifa_flags as c_int)
+}
+
+/// Convert raw `IFF_*` flags, as found in `ifa_flags` or `ifi_flags`.
+pub fn flags_from_raw(raw: c_int) -> InterfaceFlags {
+    // Haiku has no `IFF_RUNNING`; its closest equivalent, `IFF_LINK`, is not
+    // exposed by libc.
+    #[cfg(not(target_os = "haiku"))]
+    const IFF_RUNNING: c_int = libc::IFF_RUNNING;
+    #[cfg(target_os = "haiku")]
+    const IFF_RUNNING: c_int = 0;
+
+    let mapping = [
+        (libc::IFF_UP, InterfaceFlags::UP),
+        (libc::IFF_BROADCAST, InterfaceFlags::BROADCAST),
+        (libc::IFF_LOOPBACK, InterfaceFlags::LOOPBACK),
+        (libc::IFF_POINTOPOINT, InterfaceFlags::POINTOPOINT),
+        (IFF_RUNNING, InterfaceFlags::RUNNING),
+        (libc::IFF_MULTICAST, InterfaceFlags::MULTICAST),
+        (libc::IFF_PROMISC, InterfaceFlags::PROMISC),
+    ];
+
+    mapping
+        .iter()
+        .filter(|(bit, _)| raw & bit != 0)
+        .fold(InterfaceFlags::empty(), |flags, (_, flag)| flags | *flag)
+}
+
+/// Convert an `ARPHRD_*` link type, as found in `ifi_type` or `sll_hatype`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn link_type_from_arphrd(hatype: u16) -> LinkType {
+    // Not exposed by libc
+    const ARPHRD_IP6GRE: u16 = 823;
+
+    match hatype {
+        libc::ARPHRD_ETHER => LinkType::Ethernet,
+        libc::ARPHRD_LOOPBACK => LinkType::Loopback,
+        libc::ARPHRD_IEEE80211 => LinkType::Ieee80211,
+        libc::ARPHRD_PPP => LinkType::Ppp,
+        libc::ARPHRD_TUNNEL
+        | libc::ARPHRD_TUNNEL6
+        | libc::ARPHRD_SIT
+        | libc::ARPHRD_IPGRE
+        | ARPHRD_IP6GRE => LinkType::Tunnel,
+        libc::ARPHRD_CAN => LinkType::Can,
+        libc::ARPHRD_INFINIBAND => LinkType::Infiniband,
+        libc::ARPHRD_NONE => LinkType::None,
+        other => LinkType::Other(u32::from(other)),
+    }
+}
+
+/// Convert the counters of a `struct rtnl_link_stats` or
+/// `struct rtnl_link_stats64`, which share their layout up to the width of
+/// the counters. `counter(n)` returns the `n`th counter.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn stats_from_rtnl(
+    counter_width: CounterWidth,
+    counter: impl Fn(usize) -> u64,
+) -> InterfaceStats {
+    InterfaceStats {
+        index: None,
+        counter_width,
+        rx_packets: counter(0),
+        tx_packets: counter(1),
+        rx_bytes: counter(2),
+        tx_bytes: counter(3),
+        rx_errors: counter(4),
+        tx_errors: counter(5),
+        rx_dropped: counter(6),
+        tx_dropped: counter(7),
+        multicast: counter(8),
+        collisions: counter(9),
+        tx_carrier_errors: counter(17),
+    }
+}
+
+/// Read the `struct rtnl_link_stats` that glibc and bionic attach to the
+/// `AF_PACKET` entry of each interface. That entry has no address at all for
+/// interfaces without a hardware address, such as tun devices.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(unsafe_code)]
+pub fn link_stats(ifaddr: &ifaddrs) -> Option<InterfaceStats> {
+    if ifaddr.ifa_data.is_null()
+        || (!ifaddr.ifa_addr.is_null()
+            && c_int::from(unsafe { (*ifaddr.ifa_addr).sa_family }) != libc::AF_PACKET)
+    {
+        return None;
+    }
+    let counters = ifaddr.ifa_data as *const u32;
+    Some(stats_from_rtnl(CounterWidth::Bits32, |n| {
+        u64::from(unsafe { counters.add(n).read_unaligned() })
+    }))
+}
+
+/// Read the `struct if_data` attached to the `AF_LINK` entry of each
+/// interface.
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+#[allow(unsafe_code)]
+pub fn link_stats(ifaddr: &ifaddrs) -> Option<InterfaceStats> {
+    if ifaddr.ifa_addr.is_null()
+        || c_int::from(unsafe { (*ifaddr.ifa_addr).sa_family }) != libc::AF_LINK
+        || ifaddr.ifa_data.is_null()
+    {
+        return None;
+    }
+    let data = unsafe { (ifaddr.ifa_data as *const libc::if_data).read_unaligned() };
+    Some(InterfaceStats {
+        rx_bytes: u64::from(data.ifi_ibytes),
+        rx_packets: u64::from(data.ifi_ipackets),
+        rx_errors: u64::from(data.ifi_ierrors),
+        rx_dropped: u64::from(data.ifi_iqdrops),
+        tx_bytes: u64::from(data.ifi_obytes),
+        tx_packets: u64::from(data.ifi_opackets),
+        tx_errors: u64::from(data.ifi_oerrors),
+        multicast: u64::from(data.ifi_imcasts),
+        collisions: u64::from(data.ifi_collisions),
+        // FreeBSD has 64-bit counters, Apple platforms 32-bit ones
+        counter_width: if cfg!(target_os = "freebsd") {
+            CounterWidth::Bits64
+        } else {
+            CounterWidth::Bits32
+        },
+        ..Default::default()
+    })
+}
+
+/// Get the MTU of the named interface with `SIOCGIFMTU`.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+))]
+#[allow(unsafe_code)]
+pub fn mtu(name: &CStr) -> Option<u32> {
+    let mut req: libc::ifreq = unsafe { mem::zeroed() };
+    let name = name.to_bytes();
+    if name.len() >= req.ifr_name.len() {
+        return None;
+    }
+    for (dst, src) in req.ifr_name.iter_mut().zip(name) {
+        *dst = *src as libc::c_char;
+    }
+
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return None;
+    }
+    let ret = unsafe { libc::ioctl(fd, libc::SIOCGIFMTU as _, &mut req) };
+    unsafe { libc::close(fd) };
+    if ret < 0 {
+        return None;
+    }
+    u32::try_from(unsafe { req.ifr_ifru.ifru_mtu }).ok()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+pub fn mtu(_name: &CStr) -> Option<u32> {
+    None
+}
+
+pub fn do_destination(ifaddr: &ifaddrs) -> Option<IpAddr> {
     // On Linux-like systems, `ifa_ifu` is a union of `*ifa_dstaddr` and `*ifa_broadaddr`.
     #[cfg(any(
         target_os = "linux",
@@ -1,74 +1,60 @@
+use crate::if_change_notifier::Notification;
 use std::io;
 use std::mem;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
 use std::time::Duration;
 
 use libc::{
-    bind, c_int, c_void, close, recv, setsockopt, sockaddr_nl, socket, socklen_t, ssize_t, timeval,
-    AF_NETLINK, NETLINK_ROUTE, SOCK_RAW, SOL_SOCKET, SO_RCVTIMEO,
+    bind, c_int, c_void, close, recv, send, setsockopt, sockaddr_nl, socket, socklen_t, ssize_t,
+    timeval, AF_NETLINK, MSG_DONTWAIT, NETLINK_ROUTE, SOCK_RAW, SOL_SOCKET, SO_RCVTIMEO,
 };
 
+// Multicast groups of rtnetlink notifications
+pub const RTMGRP_LINK: u32 = 0x1;
+pub const RTMGRP_NEIGH: u32 = 0x4;
+pub const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+pub const RTMGRP_IPV4_ROUTE: u32 = 0x40;
+pub const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+pub const RTMGRP_IPV6_ROUTE: u32 = 0x400;
+pub const RTMGRP_IPV6_PREFIX: u32 = 0x20000;
+
 #[repr(transparent)]
-struct NetlinkSocket(c_int);
+pub struct NetlinkSocket(c_int);
 
 impl NetlinkSocket {
-    fn new() -> io::Result<Self> {
+    pub fn new() -> io::Result<Self> {
         Ok(NetlinkSocket(check_io(unsafe {
             socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE)
         })?))
     }
-}
-
-impl Drop for NetlinkSocket {
-    fn drop(&mut self) {
-        unsafe { close(self.0) };
-    }
-}
-
-fn check_io(res: c_int) -> io::Result<c_int> {
-    if res < 0 {
-        Err(io::Error::last_os_error())
-    } else {
-        Ok(res)
-    }
-}
-
-fn check_recv(res: ssize_t) -> io::Result<ssize_t> {
-    if res < 0 {
-        Err(io::Error::last_os_error())
-    } else {
-        Ok(res)
-    }
-}
-
-pub struct PosixIfChangeNotifier {
-    socket: NetlinkSocket,
-}
-
-impl PosixIfChangeNotifier {
-    pub fn new() -> io::Result<Self> {
-        let socket = NetlinkSocket::new()?;
 
+    /// Bind the socket, subscribing it to the given multicast groups.
+    pub fn bind(&self, groups: u32) -> io::Result<()> {
         let mut sockaddr: sockaddr_nl = unsafe { mem::zeroed() };
         sockaddr.nl_family = AF_NETLINK as u16;
-        sockaddr.nl_groups = 1; // RTNLGRP_LINK
+        sockaddr.nl_groups = groups;
 
         check_io(unsafe {
             bind(
-                socket.0,
+                self.0,
                 &sockaddr as *const _ as *const libc::sockaddr,
                 mem::size_of::<sockaddr_nl>() as libc::socklen_t,
             )
         })?;
+        Ok(())
+    }
 
-        Ok(Self { socket })
+    /// Send a request to the kernel.
+    pub fn send(&self, buf: &[u8]) -> io::Result<()> {
+        check_recv(unsafe { send(self.0, buf.as_ptr() as *const c_void, buf.len(), 0) })?;
+        Ok(())
     }
 
-    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<()> {
+    /// Set how long `recv` waits for a message, `None` meaning forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
         // TODO: When MSRV moves beyond Rust 1.66, this can be cleaner as
         // let mut socket = UdpSocket::from_raw_fd(socket);
         // socket.set_read_timeout(timeout)?;
-        // socket.recv(&mut buf)?;
-
         let timeout = if let Some(timeout) = timeout {
             let mut t = timeval {
                 tv_sec: timeout.as_secs().try_into().expect("timeout overflow"),
@@ -89,16 +75,117 @@ impl PosixIfChangeNotifier {
 
         check_io(unsafe {
             setsockopt(
-                self.socket.0,
+                self.0,
                 SOL_SOCKET,
                 SO_RCVTIMEO,
                 core::ptr::addr_of!(timeout) as *const _,
                 mem::size_of::<timeval>() as socklen_t,
             )
         })?;
-        let mut buf = [0u8; 65536];
-        check_recv(unsafe { recv(self.socket.0, buf.as_mut_ptr() as *mut c_void, buf.len(), 0) })?;
-
         Ok(())
     }
+
+    /// Receive a batch of messages, returning the number of bytes read.
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_with_flags(buf, 0)
+    }
+
+    fn recv_with_flags(&self, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+        let len =
+            check_recv(unsafe { recv(self.0, buf.as_mut_ptr() as *mut c_void, buf.len(), flags) })?;
+        Ok(len as usize)
+    }
+}
+
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl AsFd for NetlinkSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // The descriptor stays open until the socket is dropped
+        unsafe { BorrowedFd::borrow_raw(self.0) }
+    }
+}
+
+impl Drop for NetlinkSocket {
+    fn drop(&mut self) {
+        unsafe { close(self.0) };
+    }
+}
+
+fn check_io(res: c_int) -> io::Result<c_int> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+fn check_recv(res: ssize_t) -> io::Result<ssize_t> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+/// Check whether an error means that the socket's buffer overflowed, so
+/// notifications were lost.
+fn is_overflow(e: &io::Error) -> bool {
+    e.raw_os_error() == Some(libc::ENOBUFS)
+}
+
+pub struct PosixIfChangeNotifier {
+    socket: NetlinkSocket,
+}
+
+impl PosixIfChangeNotifier {
+    /// Create a notifier subscribed to the given multicast groups.
+    pub fn new(groups: u32) -> io::Result<Self> {
+        let socket = NetlinkSocket::new()?;
+        socket.bind(groups)?;
+
+        Ok(Self { socket })
+    }
+
+    pub fn wait<'a>(
+        &self,
+        timeout: Option<Duration>,
+        buf: &'a mut [u8],
+    ) -> io::Result<Notification<'a>> {
+        self.socket.set_read_timeout(timeout)?;
+        match self.socket.recv(buf) {
+            Ok(len) => Ok(Notification::Messages(&buf[..len])),
+            Err(e) if is_overflow(&e) => Ok(Notification::Unknown),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Receive a pending notification without blocking, if there is one.
+    pub fn try_recv<'a>(&self, buf: &'a mut [u8]) -> io::Result<Option<Notification<'a>>> {
+        loop {
+            match self.socket.recv_with_flags(buf, MSG_DONTWAIT) {
+                Ok(len) => return Ok(Some(Notification::Messages(&buf[..len]))),
+                Err(e) if is_overflow(&e) => return Ok(Some(Notification::Unknown)),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl AsRawFd for PosixIfChangeNotifier {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl AsFd for PosixIfChangeNotifier {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
 }
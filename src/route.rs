@@ -0,0 +1,514 @@
+use crate::netlink::{self, LinkMessage, NextHopMessage, RouteMessage, RouteSocket, RuleMessage};
+use crate::{GetIfAddrsOptions, IfAddr, Interface, Scope};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::RangeInclusive;
+
+/// How a route was installed, as reported by the kernel (`RTPROT_*`).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RouteProtocol {
+    /// Not specified.
+    Unspecified,
+    /// Installed by an ICMP redirect.
+    Redirect,
+    /// Installed by the kernel, e.g. for the subnet of an address.
+    Kernel,
+    /// Installed during boot, which includes routes added with `ip route`
+    /// without a protocol.
+    Boot,
+    /// Installed by the administrator.
+    Static,
+    /// Learned from an IPv6 router advertisement.
+    RouterAdvertisement,
+    /// Installed by a DHCP client.
+    Dhcp,
+    /// Any other protocol, such as a routing daemon, as the raw value.
+    Other(u8),
+}
+
+impl RouteProtocol {
+    fn from_raw(protocol: u8) -> Self {
+        match protocol {
+            0 => RouteProtocol::Unspecified,
+            1 => RouteProtocol::Redirect,
+            2 => RouteProtocol::Kernel,
+            3 => RouteProtocol::Boot,
+            4 => RouteProtocol::Static,
+            9 => RouteProtocol::RouterAdvertisement,
+            16 => RouteProtocol::Dhcp,
+            other => RouteProtocol::Other(other),
+        }
+    }
+}
+
+/// The type of a route (`RTN_*`).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RouteType {
+    /// A route to a gateway or a directly connected network.
+    Unicast,
+    /// A route to an address of this host.
+    Local,
+    /// A route to a broadcast address, sent as broadcast.
+    Broadcast,
+    /// A route to an anycast address of this host.
+    Anycast,
+    /// A multicast route.
+    Multicast,
+    /// Packets are silently discarded.
+    Blackhole,
+    /// Packets are discarded with an ICMP "unreachable" error.
+    Unreachable,
+    /// Packets are discarded with an ICMP "prohibited" error.
+    Prohibit,
+    /// Any other type, as the raw value.
+    Other(u8),
+}
+
+impl RouteType {
+    fn from_raw(ty: u8) -> Self {
+        match ty {
+            1 => RouteType::Unicast,
+            2 => RouteType::Local,
+            3 => RouteType::Broadcast,
+            4 => RouteType::Anycast,
+            5 => RouteType::Multicast,
+            6 => RouteType::Blackhole,
+            7 => RouteType::Unreachable,
+            8 => RouteType::Prohibit,
+            other => RouteType::Other(other),
+        }
+    }
+}
+
+/// (Linux/Android only) A route in one of the kernel's routing tables.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Route {
+    /// The destination network. This is the unspecified address for default
+    /// routes.
+    pub destination: IpAddr,
+    /// The prefix length of the destination network, zero for default routes.
+    pub prefixlen: u8,
+    /// The next hop, if the destination is not directly connected.
+    pub gateway: Option<IpAddr>,
+    /// The preferred source address for traffic using this route.
+    pub source: Option<IpAddr>,
+    /// The index of the output interface.
+    pub interface_index: Option<u32>,
+    /// The name of the output interface.
+    pub interface_name: Option<String>,
+    /// The metric (priority) of the route. Lower values are preferred.
+    pub metric: u32,
+    /// The routing table the route is in, such as [`Route::MAIN_TABLE`].
+    pub table: u32,
+    /// How the route was installed.
+    pub protocol: RouteProtocol,
+    /// The scope of the destination.
+    pub scope: Scope,
+    /// The type of the route.
+    pub route_type: RouteType,
+    /// The next hops of a multipath route, between which traffic is
+    /// balanced. Empty for other routes, whose single next hop is
+    /// [`Route::gateway`] on the interface [`Route::interface_index`].
+    pub nexthops: Vec<NextHop>,
+}
+
+/// (Linux/Android only) A next hop of a multipath route.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct NextHop {
+    /// The gateway, if the destination is directly connected to the
+    /// interface otherwise.
+    pub gateway: Option<IpAddr>,
+    /// The index of the output interface.
+    pub interface_index: u32,
+    /// The name of the output interface.
+    pub interface_name: Option<String>,
+    /// The share of the traffic sent through this next hop, relative to the
+    /// weights of the other next hops.
+    pub weight: u16,
+}
+
+impl NextHop {
+    fn from_message(msg: NextHopMessage, links: &HashMap<u32, LinkMessage>) -> Self {
+        Self {
+            gateway: msg.gateway,
+            interface_index: msg.oif,
+            interface_name: links.get(&msg.oif).map(|link| link.name.clone()),
+            weight: msg.weight,
+        }
+    }
+}
+
+impl Route {
+    /// The table that routes are added to by default.
+    pub const MAIN_TABLE: u32 = 254;
+    /// The table the kernel keeps routes to local and broadcast addresses in.
+    pub const LOCAL_TABLE: u32 = 255;
+
+    /// Check whether this is a default route, i.e. one matching every
+    /// destination.
+    pub fn is_default(&self) -> bool {
+        self.prefixlen == 0
+    }
+
+    fn from_message(msg: RouteMessage, links: &HashMap<u32, LinkMessage>) -> Option<Self> {
+        let destination = match (msg.destination, msg.family) {
+            (Some(destination), _) => destination,
+            (None, netlink::AF_INET) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            (None, netlink::AF_INET6) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            // Not an IP route, e.g. an MPLS route
+            (None, _) => return None,
+        };
+        Some(Self {
+            destination,
+            prefixlen: msg.dst_len,
+            gateway: msg.gateway,
+            source: msg.prefsrc,
+            interface_index: msg.oif,
+            interface_name: msg
+                .oif
+                .and_then(|index| links.get(&index))
+                .map(|link| link.name.clone()),
+            metric: msg.priority.unwrap_or(0),
+            table: msg.table,
+            protocol: RouteProtocol::from_raw(msg.protocol),
+            scope: netlink::scope_from_raw(msg.scope),
+            route_type: RouteType::from_raw(msg.ty),
+            nexthops: msg
+                .multipath
+                .into_iter()
+                .map(|hop| NextHop::from_message(hop, links))
+                .collect(),
+        })
+    }
+
+    /// Split a multipath route into a route through each of its next hops.
+    /// Other routes are returned as they are.
+    fn paths(self) -> Vec<Route> {
+        if self.nexthops.is_empty() {
+            return vec![self];
+        }
+        self.nexthops
+            .iter()
+            .map(|hop| Route {
+                gateway: hop.gateway,
+                interface_index: Some(hop.interface_index),
+                interface_name: hop.interface_name.clone(),
+                nexthops: Vec::new(),
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+/// What a routing policy rule does with the packets it matches (`FR_ACT_*`).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RuleAction {
+    /// Look the destination up in the routing table with this id, such as
+    /// [`Route::MAIN_TABLE`].
+    Lookup(u32),
+    /// Look the destination up in the routing table of the VRF (L3 master
+    /// device) that the packet's interface belongs to, as with
+    /// `ip rule add l3mdev`.
+    L3mdevLookup,
+    /// Continue with the rule with this priority.
+    Goto(u32),
+    /// Do nothing and continue with the next rule.
+    Nop,
+    /// Packets are silently discarded.
+    Blackhole,
+    /// Packets are discarded with an ICMP "unreachable" error.
+    Unreachable,
+    /// Packets are discarded with an ICMP "prohibited" error.
+    Prohibit,
+    /// Any other action, as the raw value.
+    Other(u8),
+}
+
+impl RuleAction {
+    pub(crate) fn from_message(msg: &RuleMessage) -> Self {
+        match (msg.action, msg.goto) {
+            // The table of an l3mdev rule is chosen per packet, and is zero
+            (1, _) if msg.l3mdev => RuleAction::L3mdevLookup,
+            (1, _) => RuleAction::Lookup(msg.table),
+            (2, Some(priority)) => RuleAction::Goto(priority),
+            (3, _) => RuleAction::Nop,
+            (6, _) => RuleAction::Blackhole,
+            (7, _) => RuleAction::Unreachable,
+            (8, _) => RuleAction::Prohibit,
+            (other, _) => RuleAction::Other(other),
+        }
+    }
+}
+
+/// (Linux/Android only) A routing policy rule, as listed by `ip rule`, which
+/// selects the routing table to look the destination of a packet up in.
+///
+/// A packet matches a rule if it matches all of its selectors, or none of
+/// them if the rule is inverted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Rule {
+    /// The priority of the rule. Rules are evaluated from the lowest
+    /// priority to the highest.
+    pub priority: u32,
+    /// The network that the source address must be on. This is the
+    /// unspecified address with a prefix length of zero to match any source.
+    pub source: IpAddr,
+    /// The prefix length of the source network.
+    pub source_prefixlen: u8,
+    /// The network that the destination address must be on. This is the
+    /// unspecified address with a prefix length of zero to match any
+    /// destination.
+    pub destination: IpAddr,
+    /// The prefix length of the destination network.
+    pub destination_prefixlen: u8,
+    /// The name of the interface that packets must arrive on.
+    pub input_interface_name: Option<String>,
+    /// The index of that interface, if it currently exists.
+    pub input_interface_index: Option<u32>,
+    /// The name of the interface that packets must be sent from.
+    pub output_interface_name: Option<String>,
+    /// The index of that interface, if it currently exists.
+    pub output_interface_index: Option<u32>,
+    /// The firewall mark that packets must have, after applying
+    /// [`Rule::fwmask`].
+    pub fwmark: Option<u32>,
+    /// The mask applied to the firewall mark of packets.
+    pub fwmask: Option<u32>,
+    /// The range of user ids that the sockets sending packets must belong to.
+    pub uid_range: Option<RangeInclusive<u32>>,
+    /// Whether the rule matches the packets that don't match its selectors.
+    pub invert: bool,
+    /// What the rule does with the packets it matches.
+    pub action: RuleAction,
+}
+
+impl Rule {
+    pub(crate) fn from_message(msg: RuleMessage, links: &HashMap<&str, u32>) -> Option<Self> {
+        let unspecified = match msg.family {
+            netlink::AF_INET => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            netlink::AF_INET6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            // Not an IP rule, e.g. a multicast routing rule
+            _ => return None,
+        };
+        let index =
+            |name: &Option<String>| name.as_deref().and_then(|name| links.get(name)).copied();
+        Some(Self {
+            priority: msg.priority.unwrap_or(0),
+            source: msg.source.unwrap_or(unspecified),
+            source_prefixlen: msg.src_len,
+            destination: msg.destination.unwrap_or(unspecified),
+            destination_prefixlen: msg.dst_len,
+            input_interface_index: index(&msg.iifname),
+            output_interface_index: index(&msg.oifname),
+            fwmark: msg.fwmark,
+            fwmask: msg.fwmask,
+            uid_range: msg.uid_range.map(|(start, end)| start..=end),
+            invert: msg.is_inverted(),
+            action: RuleAction::from_message(&msg),
+            input_interface_name: msg.iifname,
+            output_interface_name: msg.oifname,
+        })
+    }
+}
+
+/// (Linux/Android only) A default route, along with the address of its output
+/// interface that traffic using it is sent from.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DefaultGateway {
+    /// The default route. For a multipath route, this is the path through
+    /// one of its next hops, with that hop's gateway and output interface and
+    /// no [`Route::nexthops`].
+    pub route: Route,
+    /// The address on the output interface of the route.
+    pub interface: Interface,
+}
+
+/// (Linux/Android only) The route the kernel would use to reach a destination,
+/// as returned by [`route_to`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct RouteLookup {
+    /// The address on the output interface that traffic to the destination
+    /// is sent from.
+    pub interface: Interface,
+    /// The preferred source address for traffic to the destination.
+    pub source: IpAddr,
+    /// The next hop, if the destination is not directly connected.
+    pub gateway: Option<IpAddr>,
+}
+
+/// (Linux/Android only) Get the IPv4 and IPv6 routes in all of the kernel's
+/// routing tables.
+pub fn get_routes() -> io::Result<Vec<Route>> {
+    let mut socket = RouteSocket::new()?;
+    let links: HashMap<u32, _> = socket
+        .links()?
+        .into_iter()
+        .map(|link| (link.index, link))
+        .collect();
+    Ok(socket
+        .routes()?
+        .into_iter()
+        .filter_map(|msg| Route::from_message(msg, &links))
+        .collect())
+}
+
+/// (Linux/Android only) Get the IPv4 and IPv6 routing policy rules, each
+/// family in the order the kernel evaluates them.
+///
+/// ```no_run
+/// for rule in if_addrs::get_rules().unwrap() {
+///     println!("{}: {:?}", rule.priority, rule.action);
+/// }
+/// ```
+pub fn get_rules() -> io::Result<Vec<Rule>> {
+    let mut socket = RouteSocket::new()?;
+    let links = socket.links()?;
+    let links: HashMap<&str, u32> = links
+        .iter()
+        .map(|link| (link.name.as_str(), link.index))
+        .collect();
+    Ok(socket
+        .rules()?
+        .into_iter()
+        .filter_map(|msg| Rule::from_message(msg, &links))
+        .collect())
+}
+
+/// (Linux/Android only) Get the default routes, ordered by metric, each with
+/// the address of its output interface that matches the route: its preferred
+/// source address if it has one, otherwise an address on the same subnet as
+/// the gateway, otherwise any address of the same family. Link-local
+/// addresses only match when the interface has no other address of the
+/// family.
+///
+/// Multipath default routes are listed once for each next hop. Default
+/// routes whose output interface has no address of their family are
+/// skipped.
+///
+/// ```no_run
+/// for gateway in if_addrs::default_gateways().unwrap() {
+///     println!("{:?} via {}", gateway.route.gateway, gateway.interface.name);
+/// }
+/// ```
+pub fn default_gateways() -> io::Result<Vec<DefaultGateway>> {
+    let mut socket = RouteSocket::new()?;
+    let links: HashMap<u32, _> = socket
+        .links()?
+        .into_iter()
+        .map(|link| (link.index, link))
+        .collect();
+    let routes = socket.routes()?;
+    let interfaces = crate::get_if_addrs_with(&GetIfAddrsOptions::default())?;
+    Ok(gateways_from(routes, &links, &interfaces))
+}
+
+/// Find the default routes among `routes` and match them with `interfaces`,
+/// as [`default_gateways`] does.
+pub fn gateways_from(
+    routes: impl IntoIterator<Item = RouteMessage>,
+    links: &HashMap<u32, LinkMessage>,
+    interfaces: &[Interface],
+) -> Vec<DefaultGateway> {
+    let mut routes: Vec<_> = routes
+        .into_iter()
+        .filter_map(|msg| Route::from_message(msg, links))
+        .filter(|route| {
+            route.is_default()
+                && route.route_type == RouteType::Unicast
+                && route.table != Route::LOCAL_TABLE
+        })
+        .flat_map(Route::paths)
+        .collect();
+    routes.sort_by_key(|route| route.metric);
+
+    routes
+        .into_iter()
+        .filter_map(|route| {
+            let candidates: Vec<_> = interfaces
+                .iter()
+                .filter(|interface| {
+                    interface.index.is_some()
+                        && interface.index == route.interface_index
+                        && interface.ip().is_ipv4() == route.destination.is_ipv4()
+                })
+                .collect();
+            // The gateway of an IPv6 route is usually link-local, but traffic
+            // is sent from a global address if there is one
+            let routable: Vec<_> = candidates
+                .iter()
+                .filter(|interface| !interface.is_link_local())
+                .collect();
+            let interface = candidates
+                .iter()
+                .find(|interface| Some(interface.ip()) == route.source)
+                .or_else(|| {
+                    routable.iter().copied().find(|interface| {
+                        matches!(route.gateway, Some(gateway) if same_subnet(&interface.addr, gateway))
+                    })
+                })
+                .or_else(|| routable.first().copied())
+                .or_else(|| candidates.first())?;
+            Some(DefaultGateway {
+                interface: (*interface).clone(),
+                route,
+            })
+        })
+        .collect()
+}
+
+/// Check whether `ip` is on the subnet of `addr`.
+fn same_subnet(addr: &IfAddr, ip: IpAddr) -> bool {
+    match (addr, ip) {
+        (IfAddr::V4(addr), IpAddr::V4(ip)) => {
+            let mask = u32::from(addr.netmask);
+            u32::from(addr.ip) & mask == u32::from(ip) & mask
+        }
+        (IfAddr::V6(addr), IpAddr::V6(ip)) => {
+            let mask = u128::from(addr.netmask);
+            u128::from(addr.ip) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// (Linux/Android only) Ask the kernel which interface, source address and
+/// gateway it would use to send traffic to `destination`, like `ip route get`.
+///
+/// Destinations that are addresses of this host are routed through the
+/// loopback interface, but `interface` is the one the address is assigned to.
+/// Returns the kernel's error, such as `ENETUNREACH`, if there is no route to
+/// `destination`, and an [`io::ErrorKind::AddrNotAvailable`] error if the
+/// kernel has no source address for the route, e.g. because its output
+/// interface has no address of the family.
+///
+/// ```no_run
+/// let lookup = if_addrs::route_to("192.0.2.1".parse().unwrap()).unwrap();
+/// println!("{} via {:?} from {}", lookup.interface.name, lookup.gateway, lookup.source);
+/// ```
+pub fn route_to(destination: IpAddr) -> io::Result<RouteLookup> {
+    let msg = RouteSocket::new()?.route_to(destination)?;
+    let source = msg.prefsrc.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "the route has no source address",
+        )
+    })?;
+    let not_found = || {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "no interface has the source address of the route",
+        )
+    };
+    let interfaces = crate::get_if_addrs_with(&GetIfAddrsOptions::default())?;
+    let interface = interfaces
+        .iter()
+        .find(|interface| interface.ip() == source && interface.index == msg.oif)
+        .or_else(|| interfaces.iter().find(|interface| interface.ip() == source))
+        .ok_or_else(not_found)?;
+    Ok(RouteLookup {
+        interface: interface.clone(),
+        source,
+        gateway: msg.gateway,
+    })
+}
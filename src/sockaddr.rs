@@ -7,10 +7,23 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+#[cfg(not(windows))]
+use crate::{HardwareAddr, LinkType};
 #[cfg(not(windows))]
 use libc::{sockaddr, sockaddr_in, sockaddr_in6, AF_INET, AF_INET6};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::ptr::NonNull;
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+))]
+use std::{ptr, slice};
 #[cfg(windows)]
 use windows_sys::Win32::Networking::WinSock::{
     AF_INET, AF_INET6, SOCKADDR as sockaddr, SOCKADDR_IN as sockaddr_in,
@@ -24,6 +37,88 @@ pub fn to_ipaddr(sockaddr: *const sockaddr) -> Option<IpAddr> {
     SockAddr::new(sockaddr)?.as_ipaddr()
 }
 
+/// Extract the interface index and hardware address from a link-layer
+/// (`AF_PACKET`) sockaddr.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(unsafe_code)]
+pub fn to_hwaddr(sockaddr: *const sockaddr) -> Option<(u32, HardwareAddr)> {
+    let sa = SockAddr::new(sockaddr)?;
+    if sa.sa_family() != libc::AF_PACKET as u32 {
+        return None;
+    }
+    let sll = sa.inner.as_ptr() as *const libc::sockaddr_ll;
+    unsafe {
+        // `getifaddrs` allocates room for addresses longer than the 8 bytes
+        // of `sll_addr` (e.g. InfiniBand), and sets `sll_halen` accordingly.
+        let len = usize::from((*sll).sll_halen).min(HardwareAddr::MAX_LEN);
+        let bytes = slice::from_raw_parts(ptr::addr_of!((*sll).sll_addr) as *const u8, len);
+        Some(((*sll).sll_ifindex as u32, HardwareAddr::new(bytes)?))
+    }
+}
+
+/// Extract the link type from a link-layer (`AF_PACKET`) sockaddr.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(unsafe_code)]
+pub fn to_link_type(sockaddr: *const sockaddr) -> Option<LinkType> {
+    let sa = SockAddr::new(sockaddr)?;
+    if sa.sa_family() != libc::AF_PACKET as u32 {
+        return None;
+    }
+    let sll = sa.inner.as_ptr() as *const libc::sockaddr_ll;
+    Some(crate::posix::link_type_from_arphrd(unsafe {
+        (*sll).sll_hatype
+    }))
+}
+
+#[cfg(all(not(windows), not(any(target_os = "linux", target_os = "android"))))]
+pub fn to_link_type(_sockaddr: *const sockaddr) -> Option<LinkType> {
+    None
+}
+
+/// Extract the interface index and hardware address from a link-layer
+/// (`AF_LINK`) sockaddr.
+#[cfg(any(
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+))]
+#[allow(unsafe_code)]
+pub fn to_hwaddr(sockaddr: *const sockaddr) -> Option<(u32, HardwareAddr)> {
+    let sa = SockAddr::new(sockaddr)?;
+    if sa.sa_family() != libc::AF_LINK as u32 {
+        return None;
+    }
+    let sdl = sa.inner.as_ptr() as *const libc::sockaddr_dl;
+    unsafe {
+        // The link-layer address follows the interface name in `sdl_data`,
+        // and may extend past the end of the declared array.
+        let data = ptr::addr_of!((*sdl).sdl_data) as *const u8;
+        let bytes = slice::from_raw_parts(
+            data.add(usize::from((*sdl).sdl_nlen)),
+            usize::from((*sdl).sdl_alen),
+        );
+        Some((u32::from((*sdl).sdl_index), HardwareAddr::new(bytes)?))
+    }
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd",
+)))]
+pub fn to_hwaddr(_sockaddr: *const sockaddr) -> Option<(u32, HardwareAddr)> {
+    None
+}
+
 // Wrapper around a sockaddr pointer. Guaranteed to not be null.
 struct SockAddr {
     inner: NonNull<sockaddr>,
@@ -42,14 +137,7 @@ impl SockAddr {
                 let b = sa.sin_addr.s_addr.to_ne_bytes();
                 Some(IpAddr::V4(Ipv4Addr::new(b[0], b[1], b[2], b[3])))
             }
-            Some(SockAddrIn::In6(sa)) => {
-                // Ignore all fe80:: addresses as these are link locals
-                #[cfg(not(feature = "link-local"))]
-                if sa.sin6_addr.s6_addr[0] == 0xfe && sa.sin6_addr.s6_addr[1] == 0x80 {
-                    return None;
-                }
-                Some(IpAddr::V6(Ipv6Addr::from(sa.sin6_addr.s6_addr)))
-            }
+            Some(SockAddrIn::In6(sa)) => Some(IpAddr::V6(Ipv6Addr::from(sa.sin6_addr.s6_addr))),
             None => None,
         }
     }
@@ -59,21 +147,11 @@ impl SockAddr {
         match self.sockaddr_in() {
             Some(SockAddrIn::In(sa)) => {
                 let s_addr = unsafe { sa.sin_addr.S_un.S_addr };
-                // Ignore all 169.254.x.x addresses as these are not active interfaces
-                #[cfg(not(feature = "link-local"))]
-                if s_addr & 65535 == 0xfea9 {
-                    return None;
-                }
                 let b = s_addr.to_ne_bytes();
                 Some(IpAddr::V4(Ipv4Addr::new(b[0], b[1], b[2], b[3])))
             }
             Some(SockAddrIn::In6(sa)) => {
                 let s6_addr = unsafe { sa.sin6_addr.u.Byte };
-                // Ignore all fe80:: addresses as these are link locals
-                #[cfg(not(feature = "link-local"))]
-                if s6_addr[0] == 0xfe && s6_addr[1] == 0x80 {
-                    return None;
-                }
                 Some(IpAddr::V6(Ipv6Addr::from(s6_addr.clone())))
             }
             None => None,
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Traffic counters of an interface, as reported by the OS since the
+/// interface was created or the counters were last reset.
+///
+/// Counters that the OS doesn't report are zero.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct InterfaceStats {
+    /// The index of the interface, if known. It tells an interface apart from
+    /// one that replaced it under the same name.
+    pub index: Option<u32>,
+    /// The width of the counters, at which they wrap around.
+    pub counter_width: CounterWidth,
+    /// Bytes received.
+    pub rx_bytes: u64,
+    /// Packets received.
+    pub rx_packets: u64,
+    /// Receive errors.
+    pub rx_errors: u64,
+    /// Received packets that were dropped, e.g. for lack of buffer space.
+    pub rx_dropped: u64,
+    /// Bytes transmitted.
+    pub tx_bytes: u64,
+    /// Packets transmitted.
+    pub tx_packets: u64,
+    /// Transmit errors.
+    pub tx_errors: u64,
+    /// Packets that were dropped instead of transmitted.
+    pub tx_dropped: u64,
+    /// Multicast packets received.
+    pub multicast: u64,
+    /// Collisions detected while transmitting.
+    pub collisions: u64,
+    /// Transmit errors caused by the loss of carrier.
+    pub tx_carrier_errors: u64,
+}
+
+/// The width of the traffic counters reported by the OS.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CounterWidth {
+    /// 32-bit counters, which wrap around within minutes on a fast link.
+    Bits32,
+    /// 64-bit counters, which never wrap around in practice.
+    #[default]
+    Bits64,
+}
+
+/// Get the traffic counters of the interface with the given name, if there
+/// is one.
+pub fn get_interface_stats(name: &str) -> io::Result<Option<InterfaceStats>> {
+    Ok(get_all_interface_stats()?.remove(name))
+}
+
+/// Get the traffic counters of all the interfaces on this machine, keyed by
+/// interface name.
+///
+/// Returns an [`io::ErrorKind::Unsupported`] error on platforms where the
+/// counters are not available.
+///
+/// ```no_run
+/// for (name, stats) in if_addrs::get_all_interface_stats().unwrap() {
+///     println!("{}: {} bytes in, {} bytes out", name, stats.rx_bytes, stats.tx_bytes);
+/// }
+/// ```
+pub fn get_all_interface_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    get_all_stats()
+}
+
+/// The rates at which the counters of an interface changed between two
+/// samples taken by a [`StatsSampler`], per second.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct InterfaceRates {
+    /// The time between the two samples.
+    pub interval: Duration,
+    /// Bytes received per second.
+    pub rx_bytes: f64,
+    /// Packets received per second.
+    pub rx_packets: f64,
+    /// Receive errors per second.
+    pub rx_errors: f64,
+    /// Received packets dropped per second.
+    pub rx_dropped: f64,
+    /// Bytes transmitted per second.
+    pub tx_bytes: f64,
+    /// Packets transmitted per second.
+    pub tx_packets: f64,
+    /// Transmit errors per second.
+    pub tx_errors: f64,
+    /// Packets dropped instead of transmitted per second.
+    pub tx_dropped: f64,
+}
+
+/// A source of time for a [`StatsSampler`].
+pub trait Clock {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Computes the traffic rates of interfaces from successive samples of their
+/// counters.
+///
+/// Interfaces are identified by name and index, so an interface that is
+/// replaced by another of the same name starts afresh. An interface that is
+/// missing from a sample is forgotten, and when it comes back its rates are
+/// only reported from the sample after that. A counter that decreases is
+/// assumed to have wrapped around if it is 32 bits wide, and to have been
+/// reset if it is 64 bits wide, which counts as no change.
+///
+/// ```no_run
+/// let mut sampler = if_addrs::StatsSampler::new();
+/// loop {
+///     for (name, rates) in sampler.sample().unwrap() {
+///         println!("{}: {:.0} B/s in, {:.0} B/s out", name, rates.rx_bytes, rates.tx_bytes);
+///     }
+///     std::thread::sleep(std::time::Duration::from_secs(1));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct StatsSampler<C = SystemClock> {
+    clock: C,
+    last: Option<(Instant, HashMap<String, InterfaceStats>)>,
+}
+
+impl StatsSampler {
+    /// Create a sampler using the system's monotonic clock.
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for StatsSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> StatsSampler<C> {
+    /// Create a sampler using the given clock.
+    pub fn with_clock(clock: C) -> Self {
+        Self { clock, last: None }
+    }
+
+    /// Sample the counters of all interfaces with
+    /// [`get_all_interface_stats`], and return the rates of each interface
+    /// since the previous sample. The first sample returns no rates.
+    pub fn sample(&mut self) -> io::Result<HashMap<String, InterfaceRates>> {
+        let stats = get_all_interface_stats()?;
+        Ok(self.update(stats))
+    }
+
+    /// Record a sample of counters obtained elsewhere, and return the rates of
+    /// each interface since the previous sample.
+    ///
+    /// If no time has passed since the previous sample, no rates are
+    /// returned and the sample is discarded.
+    pub fn update(
+        &mut self,
+        stats: HashMap<String, InterfaceStats>,
+    ) -> HashMap<String, InterfaceRates> {
+        let now = self.clock.now();
+        let mut rates = HashMap::new();
+        if let Some((then, ref last)) = self.last {
+            let interval = now.saturating_duration_since(then);
+            if interval.is_zero() {
+                return rates;
+            }
+            for (name, current) in &stats {
+                let previous = last.get(name).filter(|previous| {
+                    previous.index == current.index
+                        && previous.counter_width == current.counter_width
+                });
+                if let Some(previous) = previous {
+                    rates.insert(
+                        name.clone(),
+                        InterfaceRates::between(previous, current, interval),
+                    );
+                }
+            }
+        }
+        self.last = Some((now, stats));
+        rates
+    }
+}
+
+impl InterfaceRates {
+    fn between(previous: &InterfaceStats, current: &InterfaceStats, interval: Duration) -> Self {
+        let secs = interval.as_secs_f64();
+        let width = current.counter_width;
+        let rate =
+            |previous: u64, current: u64| counter_delta(previous, current, width) as f64 / secs;
+        Self {
+            interval,
+            rx_bytes: rate(previous.rx_bytes, current.rx_bytes),
+            rx_packets: rate(previous.rx_packets, current.rx_packets),
+            rx_errors: rate(previous.rx_errors, current.rx_errors),
+            rx_dropped: rate(previous.rx_dropped, current.rx_dropped),
+            tx_bytes: rate(previous.tx_bytes, current.tx_bytes),
+            tx_packets: rate(previous.tx_packets, current.tx_packets),
+            tx_errors: rate(previous.tx_errors, current.tx_errors),
+            tx_dropped: rate(previous.tx_dropped, current.tx_dropped),
+        }
+    }
+}
+
+/// The amount a counter increased by. A 32-bit counter that decreased has
+/// wrapped around, while a 64-bit one has been reset, e.g. by a driver reload,
+/// and how far it got before that is unknown.
+fn counter_delta(previous: u64, current: u64, width: CounterWidth) -> u64 {
+    match width {
+        _ if current >= previous => current - previous,
+        CounterWidth::Bits32 => u64::from(u32::MAX).saturating_sub(previous) + current + 1,
+        CounterWidth::Bits64 => 0,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_all_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    use crate::netlink::{self, RouteSocket};
+
+    let links = match RouteSocket::new().and_then(|mut socket| socket.links()) {
+        Ok(links) => links,
+        // Fall back to `getifaddrs` where rtnetlink dumps are restricted
+        Err(e) if netlink::is_unavailable(&e) => return getifaddrs_stats(),
+        Err(e) => return Err(e),
+    };
+    Ok(links
+        .into_iter()
+        .filter_map(|link| Some((link.name, link.stats?)))
+        .collect())
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+fn get_all_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    getifaddrs_stats()
+}
+
+#[cfg(not(any(
+    windows,
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+)))]
+fn get_all_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "interface statistics are not available on this platform",
+    ))
+}
+
+/// Read the counters that `getifaddrs` attaches to the link-layer entry of
+/// each interface.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+))]
+#[allow(unsafe_code)]
+pub fn getifaddrs_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    use crate::posix::{self, IfAddrs};
+    use std::ffi::CStr;
+
+    Ok(IfAddrs::new()?
+        .iter()
+        .filter_map(|ifaddr| {
+            let mut stats = posix::link_stats(&ifaddr)?;
+            let index = unsafe { libc::if_nametoindex(ifaddr.ifa_name) };
+            stats.index = Some(index).filter(|&index| index != 0);
+            let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) }
+                .to_string_lossy()
+                .into_owned();
+            Some((name, stats))
+        })
+        .collect())
+}
+
+#[cfg(windows)]
+fn get_all_stats() -> io::Result<HashMap<String, InterfaceStats>> {
+    use crate::windows::{if_stats, IfAddrs};
+
+    Ok(IfAddrs::new()?
+        .iter()
+        .filter_map(|ifaddr| {
+            let index = ifaddr.ipv4_index().or_else(|| ifaddr.ipv6_index())?;
+            // The adapter may have been removed since it was listed
+            let mut stats = if_stats(index).ok()?;
+            stats.index = Some(index);
+            Some((ifaddr.name(), stats))
+        })
+        .collect())
+}
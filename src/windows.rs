@@ -12,14 +12,26 @@ use std::ffi::{c_void, CStr};
 use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::time::Duration;
 use std::{io, ptr};
+
+use crate::if_change_notifier::Notification;
+use crate::{AddressFlags, HardwareAddr, InterfaceFlags, InterfaceStats, LinkType, OperState};
 use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS, HANDLE};
 use windows_sys::Win32::NetworkManagement::IpHelper::{
-    CancelMibChangeNotify2, GetAdaptersAddresses, NotifyIpInterfaceChange, GAA_FLAG_INCLUDE_PREFIX,
-    GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
-    IP_ADAPTER_ADDRESSES_LH, IP_ADAPTER_PREFIX_XP, IP_ADAPTER_UNICAST_ADDRESS_LH,
-    MIB_IPINTERFACE_ROW, MIB_NOTIFICATION_TYPE,
+    CancelMibChangeNotify2, GetAdaptersAddresses, GetIfEntry2, NotifyIpInterfaceChange,
+    GAA_FLAG_INCLUDE_PREFIX, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+    GAA_FLAG_SKIP_MULTICAST, IF_TYPE_ETHERNET_CSMACD, IF_TYPE_IEEE80211, IF_TYPE_PPP,
+    IF_TYPE_SOFTWARE_LOOPBACK, IF_TYPE_TUNNEL, IP_ADAPTER_ADDRESSES_LH, IP_ADAPTER_NO_MULTICAST,
+    IP_ADAPTER_PREFIX_XP, IP_ADAPTER_UNICAST_ADDRESS_LH, MIB_IF_ROW2, MIB_IPINTERFACE_ROW,
+    MIB_NOTIFICATION_TYPE,
+};
+use windows_sys::Win32::NetworkManagement::Ndis::{
+    IfOperStatusDormant, IfOperStatusDown, IfOperStatusLowerLayerDown, IfOperStatusNotPresent,
+    IfOperStatusTesting, IfOperStatusUp,
+};
+use windows_sys::Win32::Networking::WinSock::{
+    IpDadStateDeprecated, IpDadStateDuplicate, IpDadStateInvalid, IpDadStateTentative,
+    IpPrefixOriginManual, IpPrefixOriginWellKnown, IpSuffixOriginRandom, AF_UNSPEC,
 };
-use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
 use windows_sys::Win32::System::Memory::{
     GetProcessHeap, HeapAlloc, HeapFree, HEAP_NONE, HEAP_ZERO_MEMORY,
 };
@@ -62,6 +74,70 @@ impl IpAdapterAddresses {
         }
     }
 
+    #[allow(unsafe_code)]
+    pub fn flags(&self) -> InterfaceFlags {
+        let (if_type, oper_status, adapter_flags) = unsafe {
+            (
+                (*self.0).IfType,
+                (*self.0).OperStatus,
+                (*self.0).Anonymous2.Flags,
+            )
+        };
+
+        // Windows has no separate administrative state, so an adapter is
+        // reported as both up and running when it is operational.
+        let mut flags = InterfaceFlags::empty();
+        if oper_status == IfOperStatusUp {
+            flags |= InterfaceFlags::UP | InterfaceFlags::RUNNING;
+        }
+        match if_type {
+            IF_TYPE_SOFTWARE_LOOPBACK => flags |= InterfaceFlags::LOOPBACK,
+            IF_TYPE_PPP | IF_TYPE_TUNNEL => flags |= InterfaceFlags::POINTOPOINT,
+            IF_TYPE_ETHERNET_CSMACD | IF_TYPE_IEEE80211 => flags |= InterfaceFlags::BROADCAST,
+            _ => {}
+        }
+        if adapter_flags & IP_ADAPTER_NO_MULTICAST == 0 {
+            flags |= InterfaceFlags::MULTICAST;
+        }
+        flags
+    }
+
+    #[allow(unsafe_code)]
+    pub fn mtu(&self) -> u32 {
+        unsafe { (*self.0).Mtu }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn link_type(&self) -> LinkType {
+        match unsafe { (*self.0).IfType } {
+            IF_TYPE_ETHERNET_CSMACD => LinkType::Ethernet,
+            IF_TYPE_SOFTWARE_LOOPBACK => LinkType::Loopback,
+            IF_TYPE_IEEE80211 => LinkType::Ieee80211,
+            IF_TYPE_PPP => LinkType::Ppp,
+            IF_TYPE_TUNNEL => LinkType::Tunnel,
+            other => LinkType::Other(other),
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn oper_state(&self) -> OperState {
+        match unsafe { (*self.0).OperStatus } {
+            IfOperStatusUp => OperState::Up,
+            IfOperStatusDown => OperState::Down,
+            IfOperStatusTesting => OperState::Testing,
+            IfOperStatusDormant => OperState::Dormant,
+            IfOperStatusNotPresent => OperState::NotPresent,
+            IfOperStatusLowerLayerDown => OperState::LowerLayerDown,
+            _ => OperState::Unknown,
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn hw_addr(&self) -> Option<HardwareAddr> {
+        let (addr, len) = unsafe { (&(*self.0).PhysicalAddress, (*self.0).PhysicalAddressLength) };
+        addr.get(..len as usize).and_then(HardwareAddr::new)
+    }
+
     pub fn prefixes(&self) -> PrefixesIterator {
         PrefixesIterator {
             _head: unsafe { &*self.0 },
@@ -77,6 +153,47 @@ impl IpAdapterAddresses {
     }
 }
 
+pub fn address_flags(addr: &IP_ADAPTER_UNICAST_ADDRESS_LH) -> AddressFlags {
+    let mut flags = match addr.DadState {
+        IpDadStateTentative => AddressFlags::TENTATIVE,
+        IpDadStateDeprecated => AddressFlags::DEPRECATED,
+        // An invalid DAD state means the address can't be used either
+        IpDadStateDuplicate | IpDadStateInvalid => AddressFlags::DADFAILED,
+        _ => AddressFlags::empty(),
+    };
+    if addr.SuffixOrigin == IpSuffixOriginRandom {
+        flags |= AddressFlags::TEMPORARY;
+    }
+    if addr.PrefixOrigin == IpPrefixOriginManual || addr.PrefixOrigin == IpPrefixOriginWellKnown {
+        flags |= AddressFlags::PERMANENT;
+    }
+    flags
+}
+
+/// Get the traffic counters of the interface with the given index.
+#[allow(unsafe_code)]
+pub fn if_stats(index: u32) -> io::Result<InterfaceStats> {
+    let mut row: MIB_IF_ROW2 = unsafe { std::mem::zeroed() };
+    row.InterfaceIndex = index;
+    let ret = unsafe { GetIfEntry2(&mut row) };
+    if ret != ERROR_SUCCESS {
+        return Err(io::Error::from_raw_os_error(ret as i32));
+    }
+    // Windows counts unicast and non-unicast packets separately, and doesn't
+    // report multicast packets, collisions or carrier errors.
+    Ok(InterfaceStats {
+        rx_bytes: row.InOctets,
+        rx_packets: row.InUcastPkts + row.InNUcastPkts,
+        rx_errors: row.InErrors,
+        rx_dropped: row.InDiscards,
+        tx_bytes: row.OutOctets,
+        tx_packets: row.OutUcastPkts + row.OutNUcastPkts,
+        tx_errors: row.OutErrors,
+        tx_dropped: row.OutDiscards,
+        ..Default::default()
+    })
+}
+
 pub struct IfAddrs {
     inner: IpAdapterAddresses,
 }
@@ -261,13 +378,28 @@ impl WindowsIfChangeNotifier {
         }
     }
 
-    pub fn wait(&self, timeout: Option<Duration>) -> io::Result<()> {
+    pub fn wait<'a>(
+        &self,
+        timeout: Option<Duration>,
+        _buf: &'a mut [u8],
+    ) -> io::Result<Notification<'a>> {
         if let Some(timeout) = timeout {
             self.rx.recv_timeout(timeout)
         } else {
             self.rx.recv().map_err(RecvTimeoutError::from)
         }
-        .map_err(|_| io::Error::new(io::ErrorKind::WouldBlock, "Timed out"))
+        .map_err(|_| io::Error::new(io::ErrorKind::WouldBlock, "Timed out"))?;
+        Ok(Notification::Unknown)
+    }
+
+    /// Receive the pending notifications without blocking, if there are any.
+    /// They are merged into one, as they don't say what has changed.
+    pub fn try_recv<'a>(&self, _buf: &'a mut [u8]) -> io::Result<Option<Notification<'a>>> {
+        let mut received = false;
+        while self.rx.try_recv().is_ok() {
+            received = true;
+        }
+        Ok(received.then_some(Notification::Unknown))
     }
 }
 